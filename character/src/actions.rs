@@ -8,7 +8,7 @@ use serde::{
 	Deserialize
 };
 
-#[derive(Event, Serialize, Deserialize, Clone, Copy, Hash, Eq, PartialEq)]
+#[derive(Event, Serialize, Deserialize, Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub enum CharacterAction {
 	Idle,
 	Forward,
@@ -17,6 +17,20 @@ pub enum CharacterAction {
 	Right,
 	Sprint,
 	Jump,
+	Crouch,
+	Prone,
+	/* analog movement, the axis travels alongside the action */
+	Move,
+}
+
+impl CharacterAction {
+	/* digital action whose animation represents this action */
+	pub fn animation_action(&self) -> Self {
+		match self {
+			Self::Move => Self::Forward,
+			_ => *self,
+		}
+	}
 }
 
 #[derive(Component, Default)]
@@ -28,6 +42,7 @@ pub fn play_action_animation(
 	animated_actions: &CharacterActionAnimations,
 	action: &CharacterAction,
 ) -> Option<()> {
+	let action = &action.animation_action();
 	let animation = *animated_actions.0.get(action)?;
 	if !player.is_playing_animation(animation) {
 		match action {
@@ -53,6 +68,12 @@ pub fn play_action_animation(
 				stop_action_animation(player.as_mut(), animated_actions, CharacterAction::Left);
 				stop_action_animation(player.as_mut(), animated_actions, CharacterAction::Idle);
 			},
+			CharacterAction::Crouch => {
+				stop_action_animation(player.as_mut(), animated_actions, CharacterAction::Prone);
+			},
+			CharacterAction::Prone => {
+				stop_action_animation(player.as_mut(), animated_actions, CharacterAction::Crouch);
+			},
 			_ => ()
 		}
 		player.start(animation).repeat();
@@ -60,7 +81,7 @@ pub fn play_action_animation(
 	Some(())
 }
 
-fn stop_action_animation(player: &mut AnimationPlayer, animated_actions: &CharacterActionAnimations, action: CharacterAction) {
+pub fn stop_action_animation(player: &mut AnimationPlayer, animated_actions: &CharacterActionAnimations, action: CharacterAction) {
 	if let Some(&animation) = animated_actions.0.get(&action) {
 		player.stop(animation);
 	}
@@ -3,11 +3,46 @@ use avian3d::prelude::*;
 
 use physics::spring::SpringSystem;
 
+use super::state::{
+	CharacterState,
+	AirborneState,
+	StateHooks
+};
+
+const RING_NORMAL_TOLERANCE: f32 = 0.02;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GroundProbe {
+	#[default]
+	Ray,
+	Sphere {
+		radius: f32
+	},
+	/* a centre ray surrounded by `count` rays at `radius` */
+	Ring {
+		radius: f32,
+		count: usize
+	},
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GroundHit {
+	pub entity: Entity,
+	pub point: Vec3,
+	pub normal: Vec3,
+	pub distance: f32,
+}
+
+#[derive(Component, Clone, Copy, Debug, Default, Deref)]
+pub struct GroundInfo(pub Option<GroundHit>);
+
 #[derive(Component, Default)]
 pub struct FloatingSystem {
 	pub enable: bool,
 	pub spring: SpringSystem,
 	pub slope_critical_angle: f32,
+	pub probe: GroundProbe,
+	base_float_height: f32,
 }
 
 impl FloatingSystem {
@@ -21,25 +56,95 @@ impl FloatingSystem {
 				..default()
 			}.with_critical_dampening(*mass),
 			slope_critical_angle: max_slope_angle,
+			base_float_height: float_height,
 			..default()
 		}
 	}
-	pub fn compute_force(&self, y_velocity: f32, hit: RayHitData) -> Vec3 {
+	pub fn with_probe(mut self, probe: GroundProbe) -> Self {
+		self.probe = probe;
+		self
+	}
+	pub fn compute_force(&self, y_velocity: f32, hit: &GroundHit) -> Vec3 {
 		if self.enable {
-			if hit.normal == Vec3::ZERO || f32::to_radians(90.0) - Dir3::Y.angle_between(hit.normal) > self.slope_critical_angle {
-				return Vec3::ZERO.with_y(self.spring.compute_force(y_velocity, hit.time_of_impact - self.spring.upper_bound));
+			if hit.normal == Vec3::ZERO || self.is_walkable(hit.normal) {
+				return Vec3::ZERO.with_y(self.spring.compute_force(y_velocity, hit.distance - self.spring.upper_bound));
 			}
 		}
 		Vec3::ZERO
 	}
-	pub fn cast_ray(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, position: Vec3) -> Option<RayHitData> {
-		spatial_query.cast_ray(
-			position.with_y(position.y + self.spring.equilibrium),
+	pub fn is_walkable(&self, normal: Vec3) -> bool {
+		normal != Vec3::ZERO && f32::to_radians(90.0) - Dir3::Y.angle_between(normal) > self.slope_critical_angle
+	}
+	/* steepest walkable incline, measured from the horizontal */
+	pub fn max_slope_angle(&self) -> f32 {
+		f32::to_radians(90.0) - self.slope_critical_angle
+	}
+	pub fn probe(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, position: Vec3) -> Option<GroundHit> {
+		let origin = position.with_y(position.y + self.spring.equilibrium);
+		match self.probe {
+			GroundProbe::Ray => self.cast_ray(spatial_query, filter, origin),
+			GroundProbe::Sphere { radius } => self.cast_sphere(spatial_query, filter, origin, radius),
+			GroundProbe::Ring { radius, count } => self.cast_ring(spatial_query, filter, origin, radius, count),
+		}
+	}
+	pub fn cast_ray(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, origin: Vec3) -> Option<GroundHit> {
+		let hit = spatial_query.cast_ray(
+			origin,
+			Dir3::NEG_Y,
+			self.float_range(),
+			true,
+			filter,
+		)?;
+		Some(GroundHit {
+			entity: hit.entity,
+			point: origin + Vec3::NEG_Y * hit.time_of_impact,
+			normal: hit.normal,
+			distance: hit.time_of_impact,
+		})
+	}
+	fn cast_sphere(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, origin: Vec3, radius: f32) -> Option<GroundHit> {
+		/* start with the bottom of the sphere at the origin so distances match the ray */
+		let centre = origin + Vec3::Y * radius;
+		let hit = spatial_query.cast_shape(
+			&Collider::sphere(radius),
+			centre,
+			Quat::IDENTITY,
 			Dir3::NEG_Y,
-			self.spring.upper_bound - self.spring.lower_bound,
+			self.float_range(),
 			true,
 			filter,
-		)
+		)?;
+		Some(GroundHit {
+			entity: hit.entity,
+			point: centre + Vec3::NEG_Y * hit.time_of_impact - hit.normal1 * radius,
+			normal: hit.normal1,
+			distance: hit.time_of_impact,
+		})
+	}
+	fn cast_ring(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, origin: Vec3, radius: f32, count: usize) -> Option<GroundHit> {
+		let offsets = (0..count).map(|i| {
+			let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+			Vec3::new(angle.cos(), 0., angle.sin()) * radius
+		});
+		let hits: Vec<GroundHit> = std::iter::once(Vec3::ZERO).chain(offsets)
+			.filter_map(|offset| self.cast_ray(spatial_query, filter.clone(), origin + offset))
+			.collect();
+		let closest = *hits.iter().min_by(|x, y| x.distance.total_cmp(&y.distance))?;
+		/* only hits level with the closest one, so step edges don't tilt flat ground into a slope */
+		let normal = hits.iter()
+			.filter(|hit| hit.entity == closest.entity && hit.distance - closest.distance <= RING_NORMAL_TOLERANCE)
+			.fold(Vec3::ZERO, |sum, hit| sum + hit.normal)
+			.try_normalize()
+			.unwrap_or(closest.normal);
+		Some(GroundHit {
+			normal,
+			..closest
+		})
+	}
+	pub fn scale_float_height(&mut self, scale: f32) {
+		let float_height = self.base_float_height * scale;
+		self.spring.upper_bound = float_height;
+		self.spring.lower_bound = -float_height;
 	}
 	pub fn float_height(&self) -> f32 {
 		self.spring.equilibrium - self.spring.lower_bound
@@ -48,3 +153,19 @@ impl FloatingSystem {
 		self.spring.upper_bound - self.spring.lower_bound
 	}
 }
+
+impl StateHooks for FloatingSystem {
+	fn enter_state(&mut self, _from: CharacterState, to: CharacterState) {
+		match to {
+			CharacterState::Grounded(_) => self.enable = true,
+			/* the float spring would pull the character back down while jumping */
+			CharacterState::Airborne(AirborneState::Rising) => self.enable = false,
+			CharacterState::Airborne(AirborneState::Falling) => self.enable = true,
+			CharacterState::Climbing(_) => self.enable = false,
+			/* buoyancy takes over from the float spring */
+			CharacterState::Swimming => self.enable = false,
+			/* stays on its feet, or keeps falling */
+			CharacterState::Stunned => (),
+		}
+	}
+}
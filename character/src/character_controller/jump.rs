@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct JumpSystem {
+	pub height: f32,
+	pub max_air_jumps: u32,
+	pub coyote_time: f32,
+	pub buffer_time: f32,
+	/* fraction of upward velocity kept when the jump is released early */
+	pub release_dampening: f32,
+	held: bool,
+	released: bool,
+	rising: bool,
+	buffered: f32,
+	coyote: f32,
+	air_jumps: u32,
+}
+
+impl Default for JumpSystem {
+	fn default() -> Self {
+		Self {
+			height: 0.5,
+			max_air_jumps: 0,
+			coyote_time: 0.1,
+			buffer_time: 0.15,
+			release_dampening: 0.5,
+			held: false,
+			released: false,
+			rising: false,
+			buffered: 0.,
+			coyote: 0.,
+			air_jumps: 0,
+		}
+	}
+}
+
+impl JumpSystem {
+	pub fn new(height: f32, max_air_jumps: u32, coyote_time: f32, buffer_time: f32) -> Self {
+		debug_assert!(height >= 0. && coyote_time >= 0. && buffer_time >= 0.);
+		Self {
+			height,
+			max_air_jumps,
+			coyote_time,
+			buffer_time,
+			..default()
+		}
+	}
+	pub fn with_release_dampening(mut self, release_dampening: f32) -> Self {
+		debug_assert!((0.0..=1.0).contains(&release_dampening));
+		self.release_dampening = release_dampening;
+		self
+	}
+
+	/* called once per frame with whether jump is currently held */
+	pub fn press(&mut self, held: bool) {
+		if held && !self.held {
+			self.buffered = self.buffer_time;
+		} else if !held && self.held {
+			self.released = true;
+		}
+		self.held = held;
+	}
+
+	pub fn update(&mut self, grounded: bool, delta_seconds: f32) {
+		if grounded {
+			self.coyote = self.coyote_time;
+			self.air_jumps = self.max_air_jumps;
+		} else {
+			self.coyote = (self.coyote - delta_seconds).max(0.);
+		}
+		self.buffered = (self.buffered - delta_seconds).max(0.);
+	}
+
+	pub fn try_jump(&mut self, grounded: bool) -> bool {
+		if self.buffered <= 0. {
+			return false;
+		}
+		if grounded || self.coyote > 0. {
+			self.coyote = 0.;
+		} else if self.air_jumps > 0 {
+			self.air_jumps -= 1;
+		} else {
+			return false;
+		}
+		self.buffered = 0.;
+		self.released = !self.held;
+		self.rising = true;
+		true
+	}
+
+	/* impulse needed to reach `height` from the current vertical velocity */
+	pub fn impulse(&self, mass: f32, gravity: f32, y_velocity: f32) -> Vec3 {
+		let velocity = f32::sqrt(2. * gravity * self.height);
+		Vec3::Y * mass * (velocity - y_velocity).max(0.)
+	}
+
+	/* returns the damped vertical velocity once, when the jump is released while rising */
+	pub fn cut(&mut self, y_velocity: f32) -> Option<f32> {
+		if !self.rising {
+			return None;
+		}
+		if y_velocity <= 0. {
+			self.rising = false;
+			return None;
+		}
+		if self.released {
+			self.rising = false;
+			return Some(y_velocity * self.release_dampening);
+		}
+		None
+	}
+
+	pub fn is_rising(&self) -> bool {
+		self.rising
+	}
+}
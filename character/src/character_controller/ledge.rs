@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+
+use super::floating::FloatingSystem;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ledge {
+	/* point on the top edge, directly above the wall contact */
+	pub point: Vec3,
+	/* wall normal facing away from the ledge */
+	pub normal: Vec3,
+}
+
+impl Ledge {
+	/* right hand side when facing the wall */
+	pub fn tangent(&self) -> Vec3 {
+		(-self.normal).cross(Vec3::Y).normalize_or_zero()
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LedgeInput {
+	#[default]
+	Hold,
+	Shimmy(f32),
+	Mantle,
+	Drop,
+}
+
+#[derive(Component)]
+pub struct LedgeSystem {
+	pub enable: bool,
+	/* grabbable ledge heights measured from the feet */
+	pub min_grab_height: f32,
+	pub max_grab_height: f32,
+	pub reach_distance: f32,
+	/* hanging pose relative to the ledge */
+	pub hang_depth: f32,
+	pub hang_distance: f32,
+	pub hang_speed: f32,
+	pub shimmy_speed: f32,
+	pub mantle_duration: f32,
+	pub mantle_depth: f32,
+	pub regrab_cooldown: f32,
+	ledge: Option<Ledge>,
+	input: LedgeInput,
+	mantle: Option<(Vec3, Vec3)>,
+	elapsed: f32,
+	cooldown: f32,
+}
+
+impl Default for LedgeSystem {
+	fn default() -> Self {
+		Self {
+			enable: true,
+			min_grab_height: 0.8,
+			max_grab_height: 1.8,
+			reach_distance: 0.5,
+			hang_depth: 1.3,
+			hang_distance: 0.25,
+			hang_speed: 4.0,
+			shimmy_speed: 0.8,
+			mantle_duration: 0.6,
+			mantle_depth: 0.35,
+			regrab_cooldown: 0.4,
+			ledge: None,
+			input: LedgeInput::Hold,
+			mantle: None,
+			elapsed: 0.,
+			cooldown: 0.,
+		}
+	}
+}
+
+impl LedgeSystem {
+	pub fn new(min_grab_height: f32, max_grab_height: f32, hang_depth: f32) -> Self {
+		debug_assert!(min_grab_height <= hang_depth && hang_depth <= max_grab_height);
+		Self {
+			min_grab_height,
+			max_grab_height,
+			hang_depth,
+			..default()
+		}
+	}
+	pub fn ledge(&self) -> Option<Ledge> {
+		self.ledge
+	}
+	pub fn input(&mut self, input: LedgeInput) {
+		self.input = input;
+	}
+	pub(super) fn take_input(&mut self) -> LedgeInput {
+		std::mem::take(&mut self.input)
+	}
+	pub(super) fn tick(&mut self, delta_seconds: f32) {
+		self.cooldown = (self.cooldown - delta_seconds).max(0.);
+		self.elapsed += delta_seconds;
+	}
+
+	pub fn detect(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, floating: &FloatingSystem, position: Vec3, forward: Dir3) -> Option<Ledge> {
+		if !self.enable || self.cooldown > 0. {
+			return None;
+		}
+		let wall = spatial_query.cast_ray(
+			position + Vec3::Y * self.min_grab_height,
+			forward,
+			self.reach_distance,
+			true,
+			filter.clone(),
+		)?;
+		if wall.normal == Vec3::ZERO || floating.is_walkable(wall.normal) {
+			return None;
+		}
+		let wall_point = position + forward * wall.time_of_impact;
+		/* probe the top slightly past the wall face */
+		let top_origin = (wall_point + forward * 0.05).with_y(position.y + self.max_grab_height);
+		let top = spatial_query.cast_ray(
+			top_origin,
+			Dir3::NEG_Y,
+			self.max_grab_height - self.min_grab_height,
+			true,
+			filter,
+		)?;
+		if !floating.is_walkable(top.normal) {
+			return None;
+		}
+		Some(Ledge {
+			point: wall_point.with_y(top_origin.y - top.time_of_impact),
+			normal: wall.normal.with_y(0.).normalize_or_zero(),
+		})
+	}
+
+	pub(super) fn grab(&mut self, ledge: Ledge) {
+		self.ledge = Some(ledge);
+		self.input = LedgeInput::Hold;
+		self.mantle = None;
+	}
+	pub(super) fn release(&mut self) {
+		self.ledge = None;
+		self.mantle = None;
+		self.cooldown = self.regrab_cooldown;
+	}
+
+	pub fn hang_position(&self) -> Option<Vec3> {
+		let ledge = self.ledge?;
+		Some(ledge.point + ledge.normal * self.hang_distance - Vec3::Y * self.hang_depth)
+	}
+	/* velocity pulling the character into the hanging pose */
+	pub fn hang_velocity(&self, position: Vec3, delta_seconds: f32) -> Vec3 {
+		match self.hang_position() {
+			Some(target) => ((target - position) / delta_seconds.max(f32::EPSILON)).clamp_length_max(self.hang_speed),
+			None => Vec3::ZERO,
+		}
+	}
+
+	/* moves along the ledge, returns false when the ledge ends */
+	pub(super) fn shimmy(&mut self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, floating: &FloatingSystem, distance: f32) -> bool {
+		let (Some(ledge), Some(position)) = (self.ledge, self.hang_position()) else {
+			return false;
+		};
+		let Ok(forward) = Dir3::new(-ledge.normal) else {
+			return false;
+		};
+		let cooldown = std::mem::take(&mut self.cooldown);
+		let next = self.detect(spatial_query, filter, floating, position + ledge.tangent() * distance, forward);
+		self.cooldown = cooldown;
+		match next {
+			Some(next) => {
+				self.ledge = Some(next);
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub(super) fn start_mantle(&mut self, position: Vec3, float_height: f32) {
+		if let Some(ledge) = self.ledge {
+			let target = ledge.point - ledge.normal * self.mantle_depth + Vec3::Y * float_height;
+			self.mantle = Some((position, target));
+			self.elapsed = 0.;
+		}
+	}
+	/* climbs straight up first, then over the edge */
+	pub fn mantle_position(&self) -> Option<Vec3> {
+		let (start, target) = self.mantle?;
+		let t = (self.elapsed / self.mantle_duration).clamp(0., 1.);
+		let rise = (t / 0.6).min(1.);
+		let over = ((t - 0.6) / 0.4).max(0.);
+		let horizontal = start.lerp(target, over);
+		Some(horizontal.with_y(start.y + (target.y - start.y) * rise))
+	}
+	pub fn is_mantle_finished(&self) -> bool {
+		self.mantle.is_some() && self.elapsed >= self.mantle_duration
+	}
+}
@@ -1,73 +1,519 @@
 mod movement;
 mod jump;
 mod floating;
+mod state;
+mod step;
+mod platform;
+mod stance;
+mod ledge;
+mod swim;
 
 use movement::MovementSystem;
 use floating::FloatingSystem;
+pub use floating::{
+	GroundProbe,
+	GroundHit,
+	GroundInfo
+};
+use step::StepSystem;
+use platform::PlatformSystem;
+pub use ledge::{
+	Ledge,
+	LedgeInput,
+	LedgeSystem
+};
+pub use swim::SwimSystem;
+pub use stance::{
+	Stance,
+	StanceSystem,
+	StanceCollider,
+	StanceColliderBundle
+};
+pub use jump::JumpSystem;
+pub use state::*;
 
 use std::f32::consts::PI;
 
 use bevy::prelude::*;
 use avian3d::prelude::*;
 
-use world::SpatialTypes;
+use world::{
+	SpatialTypes,
+	WaterVolume
+};
 
 const FLOAT_HEIGHT: f32 = 0.095;
 const LINEAR_DENSITY: f32 = 5.0;
 const SLOPE_CRITICAL_ANGLE: f32 = PI / 6.0;
+const LANDING_TIME: f32 = 0.15;
 
 pub struct CharacterControllerPlugin;
 
 impl Plugin for CharacterControllerPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_systems(Update, (
+		app.add_plugins((SlopePlugin, PlatformPlugin))
+			.add_event::<StateChanged>()
+			.add_event::<StunCharacter>()
+			.add_systems(Update, (
+				controller_stun,
 				controller_floating,
+				controller_step,
+				controller_ledge,
+				controller_swim,
+				controller_stance,
+				controller_jump,
+				controller_state,
+			).chain()
+		)
+		.add_systems(Update, (
+				spawn_stance_collider,
+				update_stance_collider.after(controller_stance),
 			)
 		);
 	}
 }
 
-#[derive(Component, Default)]
-pub enum CharacterState {
-	#[default]
-	Able,
-	Unable,
-	AirBorne,
+pub struct SlopePlugin;
+
+impl Plugin for SlopePlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(Update, controller_slope
+			.after(controller_floating)
+			.before(controller_jump)
+		);
+	}
+}
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(Update, controller_platform
+			.after(controller_floating)
+			.before(controller_step)
+		);
+	}
 }
 
 #[derive(Component, Default)]
 pub struct CharacterController {
-	pub state: CharacterState,
+	pub state: StateMachine,
 	pub movement: MovementSystem,
 	pub floating: FloatingSystem,
+	pub jump: JumpSystem,
+	pub step: StepSystem,
+	pub platform: PlatformSystem,
+	pub stance: StanceSystem,
+	pub ledge: LedgeSystem,
+	pub swim: SwimSystem,
 }
 
 impl CharacterController {
 	pub fn new(mass: Mass) -> Self {
 		Self {
 			floating: FloatingSystem::new(mass, FLOAT_HEIGHT, LINEAR_DENSITY, SLOPE_CRITICAL_ANGLE),
-			movement: MovementSystem::new(2., 3.5, 245.0, -15.0),
+			movement: MovementSystem::new(2., 3.5, 245.0, -15.0).with_walk_speed(0.8),
+			state: StateMachine::new(),
 			..default()
 		}
 	}
+	pub fn with_jump(mut self, jump: JumpSystem) -> Self {
+		self.jump = jump;
+		self
+	}
+	pub fn with_stance(mut self, stance: StanceSystem) -> Self {
+		self.stance = stance;
+		self
+	}
+	pub fn with_ledge(mut self, ledge: LedgeSystem) -> Self {
+		self.ledge = ledge;
+		self
+	}
+	pub fn with_swim(mut self, swim: SwimSystem) -> Self {
+		self.swim = swim;
+		self
+	}
+	pub fn with_ground_probe(mut self, probe: GroundProbe) -> Self {
+		self.floating = self.floating.with_probe(probe);
+		self
+	}
+	pub fn is_grounded(&self) -> bool {
+		self.state.current().is_grounded()
+	}
+	/* decelerates relative to the platform being stood on */
+	pub fn stop_horizontal(&self, velocity: &mut Vec3, delta_seconds: f32) {
+		let mut relative = self.platform.relative_velocity(*velocity);
+		self.movement.stop_horizontal(&mut relative, delta_seconds);
+		*velocity = relative + self.platform.velocity();
+	}
+	/* grounded state matching the current stance */
+	pub fn stance_state(&self) -> CharacterState {
+		CharacterState::Grounded(match self.stance.stance() {
+			Stance::Standing => GroundedState::Standing,
+			Stance::Crouching => GroundedState::Crouching,
+			Stance::Prone => GroundedState::Prone,
+		})
+	}
+	fn set_stance(&mut self, stance: Stance) {
+		self.stance.apply(stance);
+		self.floating.scale_float_height(self.stance.float_scale(stance));
+		match self.state.current() {
+			CharacterState::Grounded(GroundedState::Standing | GroundedState::Crouching | GroundedState::Prone) => {
+				self.set_state(self.stance_state());
+			},
+			_ => (),
+		}
+	}
+	pub fn set_state(&mut self, state: CharacterState) {
+		let previous = self.state.current();
+		if previous == state {
+			return;
+		}
+		for hooks in self.state_hooks() {
+			hooks.exit_state(previous, state);
+		}
+		self.state.transition(state);
+		self.movement.parameters = self.state.parameters(&state);
+		for hooks in self.state_hooks() {
+			hooks.enter_state(previous, state);
+		}
+	}
+	/* subsystems told about every transition */
+	fn state_hooks(&mut self) -> [&mut dyn StateHooks; 2] {
+		[&mut self.movement, &mut self.floating]
+	}
+	pub fn stun(&mut self, duration: f32) {
+		let remaining = self.state.stun_remaining().max(duration);
+		self.set_state(CharacterState::Stunned);
+		self.state.set_stun_remaining(remaining);
+	}
 }
 
 fn controller_floating(
-	mut systems_query: Query<(&mut CharacterController, &Transform, &mut ExternalForce, &LinearVelocity)>,
-	spatial_query: SpatialQuery
+	mut systems_query: Query<(&mut CharacterController, &mut GroundInfo, &Transform, &mut ExternalForce, &mut LinearVelocity)>,
+	spatial_query: SpatialQuery,
+	time: Res<Time>,
 ) {
-	for (mut controller, transform, mut force, linear_velocity) in systems_query.iter_mut() {
+	for (mut controller, mut ground, transform, mut force, mut linear_velocity) in systems_query.iter_mut() {
 		let filter = SpatialQueryFilter::from_mask(SpatialTypes::Character);
-		if let Some(hit) = controller.floating.cast_ray(&spatial_query, filter, transform.translation) {
-			match controller.state {
-				CharacterState::AirBorne => controller.state = CharacterState::Able,
-				_ => (),
+		let hit = controller.floating.probe(&spatial_query, filter.clone(), transform.translation);
+		ground.0 = hit;
+		match controller.state.current() {
+			CharacterState::Grounded(_) if hit.is_none() => {
+				/* follow the ground down stairs instead of briefly going airborne */
+				let snap = controller.step.cast_snap_ray(&spatial_query, filter, transform.translation, controller.floating.float_range())
+					.filter(|snap| controller.floating.is_walkable(snap.normal));
+				if let Some(snap) = snap {
+					let gap = snap.time_of_impact - controller.floating.float_height();
+					linear_velocity.y = linear_velocity.y.min(controller.step.snap_velocity(gap, time.delta_seconds()));
+				} else {
+					controller.set_state(CharacterState::Airborne(AirborneState::Falling));
+				}
+			},
+			CharacterState::Airborne(_) if hit.is_some() && linear_velocity.y <= 0. => {
+				controller.set_state(CharacterState::Grounded(GroundedState::Landing));
+			},
+			CharacterState::Airborne(AirborneState::Rising) if linear_velocity.y <= 0. => {
+				controller.set_state(CharacterState::Airborne(AirborneState::Falling));
+			},
+			_ => (),
+		}
+		if let Some(hit) = hit {
+			let y_velocity = controller.platform.relative_velocity(**linear_velocity).y;
+			force.persistent = false;
+			force.apply_force(controller.floating.compute_force(y_velocity, &hit));
+		}
+	}
+}
+
+fn controller_slope(
+	mut systems_query: Query<(&mut CharacterController, &GroundInfo, &Mass, &mut ExternalForce)>,
+	gravity: Res<Gravity>,
+) {
+	for (mut controller, ground, mass, mut force) in systems_query.iter_mut() {
+		let normal = match **ground {
+			Some(hit) if controller.is_grounded() && hit.normal != Vec3::ZERO => hit.normal,
+			_ => Vec3::Y,
+		};
+		let max_slope_angle = controller.floating.max_slope_angle();
+		controller.movement.set_ground(normal, max_slope_angle);
+		let walkable = controller.floating.is_walkable(normal);
+		match controller.state.current() {
+			CharacterState::Grounded(GroundedState::Sliding) if walkable => {
+				let state = controller.stance_state();
+				controller.set_state(state);
+			},
+			CharacterState::Grounded(_) if !walkable => {
+				controller.set_state(CharacterState::Grounded(GroundedState::Sliding));
+			},
+			_ => (),
+		}
+		if let CharacterState::Grounded(GroundedState::Sliding) = controller.state.current() {
+			force.persistent = false;
+			force.apply_force(controller.movement.slide_force(mass.0, gravity.0));
+		}
+	}
+}
+
+fn controller_platform(
+	mut systems_query: Query<(&mut CharacterController, &GroundInfo, &mut Transform, &mut LinearVelocity)>,
+	collider_query: Query<&ColliderParent>,
+	platform_query: Query<(&LinearVelocity, &AngularVelocity, &Position, &Rotation, &CenterOfMass), Without<CharacterController>>,
+	time: Res<Time>,
+) {
+	for (mut controller, ground, mut transform, mut linear_velocity) in systems_query.iter_mut() {
+		let platform = match **ground {
+			Some(hit) if controller.platform.enable && controller.is_grounded() => {
+				let body = collider_query.get(hit.entity).map_or(hit.entity, |parent| parent.get());
+				platform_query.get(body).ok().map(|(velocity, angular_velocity, position, rotation, center_of_mass)| {
+					let centre = position.0 + rotation.0 * center_of_mass.0;
+					(body, PlatformSystem::point_velocity(velocity.0, angular_velocity.0, centre, hit.point), angular_velocity.0)
+				})
+			},
+			_ => None,
+		};
+		if let Some((body, velocity, angular_velocity)) = platform {
+			linear_velocity.0 += controller.platform.carry(body, velocity, angular_velocity);
+			if controller.platform.inherit_rotation {
+				transform.rotate_y(angular_velocity.y * time.delta_seconds());
 			}
+		} else if controller.platform.platform().is_some() {
+			controller.platform.release();
+		}
+	}
+}
+
+fn controller_step(
+	mut systems_query: Query<(&CharacterController, &Transform, &mut LinearVelocity)>,
+	spatial_query: SpatialQuery,
+) {
+	for (controller, transform, mut linear_velocity) in systems_query.iter_mut() {
+		if !controller.is_grounded() {
+			continue;
+		}
+		let horizontal = linear_velocity.with_y(0.);
+		let Ok(direction) = Dir3::new(horizontal) else {
+			continue;
+		};
+		let filter = SpatialQueryFilter::from_mask(SpatialTypes::World);
+		if let Some((step_height, distance)) = controller.step.detect_step(&spatial_query, filter, &controller.floating, transform.translation, direction) {
+			let y_velocity = controller.step.step_velocity(step_height, distance, horizontal.length());
+			linear_velocity.y = linear_velocity.y.max(y_velocity);
+		}
+	}
+}
+
+fn controller_ledge(
+	mut systems_query: Query<(&mut CharacterController, &Mass, &mut Transform, &mut LinearVelocity, &mut ExternalForce)>,
+	spatial_query: SpatialQuery,
+	gravity: Res<Gravity>,
+	time: Res<Time>,
+) {
+	for (mut controller, mass, mut transform, mut linear_velocity, mut force) in systems_query.iter_mut() {
+		let filter = SpatialQueryFilter::from_mask(SpatialTypes::World);
+		let delta_seconds = time.delta_seconds();
+		controller.ledge.tick(delta_seconds);
+		match controller.state.current() {
+			/* grabbing on the way up would cancel jumps next to walls */
+			CharacterState::Airborne(AirborneState::Falling) => {
+				let Ok(forward) = Dir3::new(linear_velocity.with_y(0.)) else {
+					continue;
+				};
+				if let Some(ledge) = controller.ledge.detect(&spatial_query, filter, &controller.floating, transform.translation, forward) {
+					controller.ledge.grab(ledge);
+					controller.set_state(CharacterState::Climbing(ClimbingState::Hanging));
+				}
+				continue;
+			},
+			CharacterState::Climbing(ClimbingState::Hanging) => {
+				match controller.ledge.take_input() {
+					LedgeInput::Drop => {
+						controller.ledge.release();
+						controller.set_state(CharacterState::Airborne(AirborneState::Falling));
+						continue;
+					},
+					LedgeInput::Mantle => {
+						let float_height = controller.floating.float_height();
+						controller.ledge.start_mantle(transform.translation, float_height);
+						controller.set_state(CharacterState::Climbing(ClimbingState::Mantling));
+					},
+					LedgeInput::Shimmy(axis) => {
+						let controller = controller.as_mut();
+						let distance = axis.clamp(-1., 1.) * controller.ledge.shimmy_speed * delta_seconds;
+						controller.ledge.shimmy(&spatial_query, filter, &controller.floating, distance);
+					},
+					LedgeInput::Hold => (),
+				}
+				linear_velocity.0 = controller.ledge.hang_velocity(transform.translation, delta_seconds);
+			},
+			CharacterState::Climbing(ClimbingState::Mantling) => {
+				linear_velocity.0 = Vec3::ZERO;
+				if let Some(position) = controller.ledge.mantle_position() {
+					transform.translation = position;
+				}
+				if controller.ledge.is_mantle_finished() {
+					controller.ledge.release();
+					let state = controller.stance_state();
+					controller.set_state(state);
+				}
+			},
+			_ => continue,
+		}
+		/* hold the body up against gravity while on the ledge */
+		force.persistent = false;
+		force.apply_force(-gravity.0 * mass.0);
+	}
+}
+
+fn controller_swim(
+	mut systems_query: Query<(&mut CharacterController, &GroundInfo, &Transform, &Mass, &LinearVelocity, &mut ExternalForce)>,
+	water_query: Query<(&WaterVolume, &ColliderAabb)>,
+	spatial_query: SpatialQuery,
+	gravity: Res<Gravity>,
+) {
+	for (mut controller, ground, transform, mass, linear_velocity, mut force) in systems_query.iter_mut() {
+		let filter = SpatialQueryFilter::from_mask(SpatialTypes::Water);
+		let water = spatial_query.point_intersections(transform.translation, filter)
+			.into_iter()
+			.find_map(|entity| water_query.get(entity).ok());
+		let surface = water.map_or(transform.translation.y, |(_, aabb)| aabb.max.y);
+		controller.swim.update(water.map(|(&volume, _)| volume), transform.translation.y, surface, mass.0);
+		/* touching the bottom of deep water keeps swimming, wading in deep enough starts */
+		match controller.state.current() {
+			CharacterState::Swimming if ground.is_some() && !controller.swim.should_swim() => {
+				controller.set_state(CharacterState::Grounded(GroundedState::Landing));
+			},
+			CharacterState::Swimming if water.is_none() => {
+				controller.set_state(CharacterState::Airborne(AirborneState::Falling));
+			},
+			CharacterState::Airborne(_) if ground.is_none() && controller.swim.should_swim() => {
+				controller.set_state(CharacterState::Swimming);
+			},
+			CharacterState::Grounded(_) if controller.swim.should_swim() => {
+				controller.set_state(CharacterState::Swimming);
+			},
+			_ => (),
+		}
+		if let CharacterState::Swimming = controller.state.current() {
 			force.persistent = false;
-			force.apply_force(controller.floating.compute_force(linear_velocity.y, hit));
+			force.apply_force(controller.swim.compute_force(mass.0, gravity.0, **linear_velocity));
+		}
+	}
+}
+
+fn spawn_stance_collider(
+	mut commands: Commands,
+	controller_query: Query<(Entity, &CharacterController), Added<CharacterController>>,
+) {
+	for (entity, controller) in controller_query.iter() {
+		commands.entity(entity).with_children(|parent| {
+			parent.spawn(StanceColliderBundle::new(&controller.stance));
+		});
+	}
+}
+
+fn update_stance_collider(
+	controller_query: Query<(&CharacterController, &Children)>,
+	mut collider_query: Query<(&mut StanceCollider, &mut Collider)>,
+) {
+	for (controller, children) in controller_query.iter() {
+		let stance = controller.stance.stance();
+		for &child in children.iter() {
+			let Ok((mut current, mut collider)) = collider_query.get_mut(child) else {
+				continue;
+			};
+			if current.0 != stance {
+				current.0 = stance;
+				*collider = controller.stance.body_collider(stance);
+			}
+		}
+	}
+}
+
+fn controller_stance(
+	mut systems_query: Query<(&mut CharacterController, &Transform)>,
+	spatial_query: SpatialQuery,
+) {
+	for (mut controller, transform) in systems_query.iter_mut() {
+		let requested = if controller.is_grounded() {
+			controller.stance.requested()
 		} else {
-			controller.state = CharacterState::AirBorne;
-			controller.floating.enable = true;
+			Stance::Standing
+		};
+		if requested == controller.stance.stance() {
+			continue;
+		}
+		let filter = SpatialQueryFilter::from_mask(SpatialTypes::World);
+		if controller.stance.has_clearance(&spatial_query, filter, transform.translation, requested) {
+			controller.set_stance(requested);
+		}
+	}
+}
+
+fn controller_jump(
+	mut systems_query: Query<(&mut CharacterController, &Mass, &mut ExternalImpulse, &mut LinearVelocity)>,
+	gravity: Res<Gravity>,
+	time: Res<Time>,
+) {
+	for (mut controller, mass, mut impulse, mut linear_velocity) in systems_query.iter_mut() {
+		let grounded = controller.is_grounded();
+		/* jumping while on a ledge mantles and while swimming ascends instead, stunned characters can't */
+		if matches!(controller.state.current(), CharacterState::Climbing(_) | CharacterState::Swimming | CharacterState::Stunned) {
+			controller.jump.update(false, time.delta_seconds());
+			continue;
+		}
+		if controller.jump.try_jump(grounded) {
+			controller.set_state(CharacterState::Airborne(AirborneState::Rising));
+			impulse.persistent = false;
+			impulse.apply_impulse(controller.jump.impulse(mass.0, gravity.0.length(), linear_velocity.y));
+		} else if let Some(y_velocity) = controller.jump.cut(linear_velocity.y) {
+			linear_velocity.y = y_velocity;
+		}
+		controller.jump.update(grounded, time.delta_seconds());
+	}
+}
+
+fn controller_stun(
+	mut stun_events: EventReader<StunCharacter>,
+	mut systems_query: Query<&mut CharacterController>,
+) {
+	for event in stun_events.read() {
+		if let Ok(mut controller) = systems_query.get_mut(event.entity) {
+			controller.stun(event.duration);
+		}
+	}
+}
+
+fn controller_state(
+	mut commands: Commands,
+	mut systems_query: Query<(Entity, &mut CharacterController, &LinearVelocity)>,
+	mut state_events: EventWriter<StateChanged>,
+	time: Res<Time>,
+) {
+	for (entity, mut controller, linear_velocity) in systems_query.iter_mut() {
+		controller.state.tick(time.delta_seconds());
+		match controller.state.current() {
+			CharacterState::Grounded(GroundedState::Landing) if controller.state.elapsed() >= LANDING_TIME => {
+				let state = controller.stance_state();
+				controller.set_state(state);
+			},
+			/* the floating probe puts characters stunned in the air back to falling */
+			CharacterState::Stunned if controller.state.stun_remaining() <= 0. => {
+				let state = controller.stance_state();
+				controller.set_state(state);
+			},
+			_ => (),
+		}
+		for (from, to) in controller.state.drain_changes() {
+			let event = StateChanged {
+				entity,
+				from,
+				to,
+				velocity: linear_velocity.0,
+			};
+			state_events.send(event);
+			/* observers on the character hook into its transitions */
+			commands.trigger_targets(event, entity);
 		}
 	}
 }
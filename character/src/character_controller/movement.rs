@@ -1,21 +1,45 @@
+use std::f32::consts::PI;
+
 use bevy::prelude::*;
 
+use super::state::{
+	CharacterState,
+	MovementParameters,
+	StateHooks
+};
+
 #[derive(Component)]
 pub struct MovementSystem {
+	pub walk_speed: f32,
 	pub max_run_speed: f32,
 	pub max_sprint_speed: f32,
 	pub acceleration: f32,
 	pub deceleration: f32,
+	pub parameters: MovementParameters,
+	/* speed multipliers reached at the steepest walkable slope */
+	pub uphill_speed: f32,
+	pub downhill_speed: f32,
+	/* fraction of gravity along the slope applied while sliding */
+	pub slide_acceleration: f32,
+	ground_normal: Vec3,
+	max_slope_angle: f32,
 	previous_velocity: Vec3,
 }
 
 impl Default for MovementSystem {
 	fn default() -> Self {
 		Self {
+			walk_speed: 0.5,
 			max_run_speed: 1.0,
 			max_sprint_speed: 1.5,
 			acceleration: 1.0,
 			deceleration: 1.0,
+			parameters: MovementParameters::default(),
+			uphill_speed: 0.6,
+			downhill_speed: 1.2,
+			slide_acceleration: 1.0,
+			ground_normal: Vec3::Y,
+			max_slope_angle: PI / 3.0,
 			previous_velocity: Vec3::ZERO,
 		}
 	}
@@ -33,6 +57,39 @@ impl MovementSystem {
 			..default()
 		}
 	}
+	pub fn with_walk_speed(mut self, walk_speed: f32) -> Self {
+		debug_assert!((0. ..=self.max_run_speed).contains(&walk_speed));
+		self.walk_speed = walk_speed;
+		self
+	}
+
+	pub fn set_ground(&mut self, normal: Vec3, max_slope_angle: f32) {
+		debug_assert!(max_slope_angle > 0.);
+		self.ground_normal = normal.try_normalize().unwrap_or(Vec3::Y);
+		self.max_slope_angle = max_slope_angle;
+	}
+
+	pub fn ground_normal(&self) -> Vec3 {
+		self.ground_normal
+	}
+
+	/* direction along the ground plane and the speed multiplier for its incline */
+	pub fn project_on_ground(&self, direction: Dir3) -> (Dir3, f32) {
+		let projected = Dir3::new(direction.reject_from_normalized(self.ground_normal)).unwrap_or(direction);
+		let incline = projected.y.clamp(-1., 1.).asin();
+		let t = (incline.abs() / self.max_slope_angle).min(1.);
+		let speed = if incline > 0. {
+			1. + (self.uphill_speed - 1.) * t
+		} else {
+			1. + (self.downhill_speed - 1.) * t
+		};
+		(projected, speed)
+	}
+
+	pub fn slide_force(&self, mass: f32, gravity: Vec3) -> Vec3 {
+		gravity.reject_from_normalized(self.ground_normal) * mass * self.slide_acceleration
+	}
+
 	pub fn reset_velocity(&mut self) -> Vec3 {
 		let previous_velocity = self.previous_velocity;
 		self.previous_velocity = Vec3::ZERO;
@@ -48,7 +105,9 @@ impl MovementSystem {
 	}
 
 	fn update_velocity(&mut self, direction: Dir3, delta_seconds: f32, max_speed: f32) -> Vec3 {
-		let mut velocity = (direction * self.acceleration * delta_seconds).clamp_length_max(max_speed);
+		let (direction, slope_speed) = self.project_on_ground(direction);
+		let max_speed = max_speed * self.parameters.speed * slope_speed;
+		let mut velocity = (direction * self.acceleration * self.parameters.acceleration * delta_seconds).clamp_length_max(max_speed);
 		dbg!(velocity.length());
 		dbg!(self.previous_velocity.length());
 		if self.previous_velocity != Vec3::ZERO {
@@ -96,9 +155,12 @@ impl MovementSystem {
 	}
 
 	fn update_impulse(&mut self, velocity: Vec3, direction: Dir3, max_speed: f32) -> Vec3 {
-		let impulse = direction * self.acceleration;
+		let (direction, slope_speed) = self.project_on_ground(direction);
+		let max_speed = max_speed * self.parameters.speed * slope_speed;
+		let impulse = direction * self.acceleration * self.parameters.acceleration;
 		if velocity.distance_squared(impulse) > velocity.length_squared() - impulse.length_squared() {
-			impulse.clamp_length_max(max_speed - velocity.length())
+			/* already faster than the limit, as when slowing from a run to a walk */
+			impulse.clamp_length_max((max_speed - velocity.length()).max(0.))
 		} else {
 			Vec3::ZERO
 		}
@@ -117,6 +179,16 @@ impl MovementSystem {
 		}
 	}
 
+	/* scales between walking and running with the analog magnitude */
+	pub fn update_analog_impulse_horizontal(&mut self, velocity: Vec3, direction: Vec3, magnitude: f32) -> Vec3 {
+		if let Ok(direction) = Dir3::new(direction.with_y(0.)) {
+			let max_speed = self.walk_speed + (self.max_run_speed - self.walk_speed) * magnitude.clamp(0., 1.);
+			self.update_impulse(velocity, direction, max_speed)
+		} else {
+			Vec3::ZERO
+		}
+	}
+
 	pub fn update_sprint_impulse(&mut self, velocity: Vec3, direction: Dir3) -> Vec3 {
 		self.update_impulse(velocity, direction, self.max_sprint_speed)
 	}
@@ -129,3 +201,11 @@ impl MovementSystem {
 		}
 	}
 }
+
+impl StateHooks for MovementSystem {
+	fn exit_state(&mut self, from: CharacterState, to: CharacterState) {
+		if from.is_airborne() && !from.is_sibling(&to) {
+			self.reset_velocity();
+		}
+	}
+}
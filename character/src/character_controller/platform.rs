@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct PlatformSystem {
+	pub enable: bool,
+	/* whether rotating platforms also turn the character */
+	pub inherit_rotation: bool,
+	platform: Option<Entity>,
+	velocity: Vec3,
+	angular_velocity: Vec3,
+}
+
+impl Default for PlatformSystem {
+	fn default() -> Self {
+		Self {
+			enable: true,
+			inherit_rotation: true,
+			platform: None,
+			velocity: Vec3::ZERO,
+			angular_velocity: Vec3::ZERO,
+		}
+	}
+}
+
+impl PlatformSystem {
+	pub fn platform(&self) -> Option<Entity> {
+		self.platform
+	}
+	/* velocity of the platform at the contact point, zero when not on a platform */
+	pub fn velocity(&self) -> Vec3 {
+		self.velocity
+	}
+	pub fn angular_velocity(&self) -> Vec3 {
+		self.angular_velocity
+	}
+	pub fn point_velocity(linear_velocity: Vec3, angular_velocity: Vec3, centre: Vec3, point: Vec3) -> Vec3 {
+		linear_velocity + angular_velocity.cross(point - centre)
+	}
+	/* returns the change in velocity the character should inherit this frame */
+	pub fn carry(&mut self, platform: Entity, velocity: Vec3, angular_velocity: Vec3) -> Vec3 {
+		let previous = self.velocity;
+		self.platform = Some(platform);
+		self.velocity = velocity;
+		self.angular_velocity = angular_velocity;
+		velocity - previous
+	}
+	/* leaving a platform keeps its momentum, so nothing is taken away here */
+	pub fn release(&mut self) {
+		self.platform = None;
+		self.velocity = Vec3::ZERO;
+		self.angular_velocity = Vec3::ZERO;
+	}
+	pub fn relative_velocity(&self, velocity: Vec3) -> Vec3 {
+		velocity - self.velocity
+	}
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+
+use world::SpatialTypes;
+
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum Stance {
+	#[default]
+	Standing,
+	Crouching,
+	Prone,
+}
+
+/* the body capsule shrinks to the stance height, so crouching and prone fit under low ceilings */
+#[derive(Component)]
+pub struct StanceSystem {
+	pub standing_height: f32,
+	pub crouching_height: f32,
+	pub prone_height: f32,
+	pub body_radius: f32,
+	/* float height multipliers relative to standing */
+	pub crouching_float_scale: f32,
+	pub prone_float_scale: f32,
+	pub clearance_radius: f32,
+	stance: Stance,
+	requested: Stance,
+}
+
+impl Default for StanceSystem {
+	fn default() -> Self {
+		Self {
+			standing_height: 1.5,
+			crouching_height: 0.9,
+			prone_height: 0.4,
+			body_radius: 0.2,
+			crouching_float_scale: 0.6,
+			prone_float_scale: 0.3,
+			clearance_radius: 0.15,
+			stance: Stance::Standing,
+			requested: Stance::Standing,
+		}
+	}
+}
+
+impl StanceSystem {
+	pub fn new(standing_height: f32, crouching_height: f32, prone_height: f32) -> Self {
+		debug_assert!(standing_height >= crouching_height && crouching_height >= prone_height && prone_height > 0.);
+		Self {
+			standing_height,
+			crouching_height,
+			prone_height,
+			..default()
+		}
+	}
+	pub fn stance(&self) -> Stance {
+		self.stance
+	}
+	pub fn requested(&self) -> Stance {
+		self.requested
+	}
+	pub fn request(&mut self, stance: Stance) {
+		self.requested = stance;
+	}
+	pub fn height(&self, stance: Stance) -> f32 {
+		match stance {
+			Stance::Standing => self.standing_height,
+			Stance::Crouching => self.crouching_height,
+			Stance::Prone => self.prone_height,
+		}
+	}
+	/* capsule from above the float gap up to the stance height, narrowed for low stances */
+	pub fn body_collider(&self, stance: Stance) -> Collider {
+		let height = self.height(stance);
+		let radius = self.body_radius.min(height / 4.);
+		Collider::capsule_endpoints(radius, Vec3::Y * (2. * radius), Vec3::Y * (height - radius))
+	}
+	pub fn float_scale(&self, stance: Stance) -> f32 {
+		match stance {
+			Stance::Standing => 1.0,
+			Stance::Crouching => self.crouching_float_scale,
+			Stance::Prone => self.prone_float_scale,
+		}
+	}
+	/* whether there is room above `position` to grow into `stance` */
+	pub fn has_clearance(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, position: Vec3, stance: Stance) -> bool {
+		let current = self.height(self.stance);
+		let target = self.height(stance);
+		if target <= current {
+			return true;
+		}
+		spatial_query.cast_shape(
+			&Collider::sphere(self.clearance_radius),
+			position + Vec3::Y * (current - self.clearance_radius),
+			Quat::IDENTITY,
+			Dir3::Y,
+			target - current,
+			true,
+			filter,
+		).is_none()
+	}
+	pub(super) fn apply(&mut self, stance: Stance) {
+		self.stance = stance;
+	}
+}
+
+/* the body volume of a character, a child of the controller sized by its stance */
+#[derive(Component, Clone, Copy, Debug)]
+pub struct StanceCollider(pub Stance);
+
+#[derive(Bundle)]
+pub struct StanceColliderBundle {
+	pub stance: StanceCollider,
+	pub collider: Collider,
+	/* the body volume leaves the character's mass alone */
+	pub density: ColliderDensity,
+	pub layers: CollisionLayers,
+	pub transform: TransformBundle,
+}
+
+impl StanceColliderBundle {
+	pub fn new(system: &StanceSystem) -> Self {
+		Self {
+			stance: StanceCollider(system.stance()),
+			collider: system.body_collider(system.stance()),
+			density: ColliderDensity(0.),
+			layers: CollisionLayers::new(SpatialTypes::Body, SpatialTypes::World),
+			transform: TransformBundle::default(),
+		}
+	}
+}
@@ -0,0 +1,171 @@
+use bevy::{
+	prelude::*,
+	utils::HashMap
+};
+
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum CharacterState {
+	Grounded(GroundedState),
+	Airborne(AirborneState),
+	Climbing(ClimbingState),
+	Stunned,
+	Swimming,
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum GroundedState {
+	#[default]
+	Standing,
+	Landing,
+	Crouching,
+	Prone,
+	Sliding,
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum AirborneState {
+	#[default]
+	Rising,
+	Falling,
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum ClimbingState {
+	#[default]
+	Hanging,
+	Mantling,
+}
+
+impl Default for CharacterState {
+	fn default() -> Self {
+		Self::Grounded(GroundedState::default())
+	}
+}
+
+impl CharacterState {
+	pub fn is_grounded(&self) -> bool {
+		matches!(self, Self::Grounded(_))
+	}
+	pub fn is_airborne(&self) -> bool {
+		matches!(self, Self::Airborne(_))
+	}
+	pub fn is_climbing(&self) -> bool {
+		matches!(self, Self::Climbing(_))
+	}
+	/* whether both states belong to the same top level state */
+	pub fn is_sibling(&self, other: &Self) -> bool {
+		std::mem::discriminant(self) == std::mem::discriminant(other)
+	}
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StateChanged {
+	pub entity: Entity,
+	pub from: CharacterState,
+	pub to: CharacterState,
+	/* at the end of the controller systems, before physics responds, so a landing still carries its impact */
+	pub velocity: Vec3,
+}
+
+/* takes control away from a character for the duration, stunning again extends it */
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StunCharacter {
+	pub entity: Entity,
+	pub duration: f32,
+}
+
+/* controller subsystems react to transitions through this, gameplay observes StateChanged on the character */
+pub trait StateHooks {
+	fn exit_state(&mut self, _from: CharacterState, _to: CharacterState) {}
+	fn enter_state(&mut self, _from: CharacterState, _to: CharacterState) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementParameters {
+	pub speed: f32,
+	pub acceleration: f32,
+}
+
+impl Default for MovementParameters {
+	fn default() -> Self {
+		Self {
+			speed: 1.0,
+			acceleration: 1.0,
+		}
+	}
+}
+
+impl MovementParameters {
+	pub fn new(speed: f32, acceleration: f32) -> Self {
+		debug_assert!(speed >= 0. && acceleration >= 0.);
+		Self {
+			speed,
+			acceleration,
+		}
+	}
+}
+
+#[derive(Default)]
+pub struct StateMachine {
+	current: CharacterState,
+	previous: CharacterState,
+	elapsed: f32,
+	stun_duration: f32,
+	changes: Vec<(CharacterState, CharacterState)>,
+	pub parameters: HashMap<CharacterState, MovementParameters>,
+}
+
+impl StateMachine {
+	pub fn new() -> Self {
+		let mut parameters = HashMap::new();
+		parameters.insert(CharacterState::Grounded(GroundedState::Landing), MovementParameters::new(0.6, 0.6));
+		parameters.insert(CharacterState::Grounded(GroundedState::Crouching), MovementParameters::new(0.5, 1.0));
+		parameters.insert(CharacterState::Grounded(GroundedState::Prone), MovementParameters::new(0.25, 0.5));
+		parameters.insert(CharacterState::Grounded(GroundedState::Sliding), MovementParameters::new(1.0, 0.2));
+		parameters.insert(CharacterState::Airborne(AirborneState::Rising), MovementParameters::new(1.0, 0.4));
+		parameters.insert(CharacterState::Airborne(AirborneState::Falling), MovementParameters::new(1.0, 0.4));
+		parameters.insert(CharacterState::Climbing(ClimbingState::Hanging), MovementParameters::new(0., 0.));
+		parameters.insert(CharacterState::Climbing(ClimbingState::Mantling), MovementParameters::new(0., 0.));
+		parameters.insert(CharacterState::Stunned, MovementParameters::new(0., 0.));
+		parameters.insert(CharacterState::Swimming, MovementParameters::new(0.6, 0.5));
+		Self {
+			parameters,
+			..default()
+		}
+	}
+	pub fn current(&self) -> CharacterState {
+		self.current
+	}
+	pub fn previous(&self) -> CharacterState {
+		self.previous
+	}
+	/* seconds spent in the current state */
+	pub fn elapsed(&self) -> f32 {
+		self.elapsed
+	}
+	/* seconds left before a stun wears off */
+	pub fn stun_remaining(&self) -> f32 {
+		match self.current {
+			CharacterState::Stunned => (self.stun_duration - self.elapsed).max(0.),
+			_ => 0.,
+		}
+	}
+	pub fn parameters(&self, state: &CharacterState) -> MovementParameters {
+		self.parameters.get(state).copied().unwrap_or_default()
+	}
+	pub(super) fn transition(&mut self, state: CharacterState) {
+		self.changes.push((self.current, state));
+		self.previous = self.current;
+		self.current = state;
+		self.elapsed = 0.;
+	}
+	pub(super) fn set_stun_remaining(&mut self, duration: f32) {
+		self.stun_duration = self.elapsed + duration;
+	}
+	pub(super) fn tick(&mut self, delta_seconds: f32) {
+		self.elapsed += delta_seconds;
+	}
+	pub(super) fn drain_changes(&mut self) -> impl Iterator<Item = (CharacterState, CharacterState)> + '_ {
+		self.changes.drain(..)
+	}
+}
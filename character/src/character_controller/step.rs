@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+
+use super::floating::FloatingSystem;
+
+#[derive(Component)]
+pub struct StepSystem {
+	pub enable: bool,
+	pub max_step_height: f32,
+	pub probe_radius: f32,
+	pub probe_distance: f32,
+	pub max_step_speed: f32,
+	/* how far below the float range the ground is still followed when descending */
+	pub snap_distance: f32,
+	pub max_snap_speed: f32,
+}
+
+impl Default for StepSystem {
+	fn default() -> Self {
+		Self {
+			enable: true,
+			max_step_height: 0.25,
+			probe_radius: 0.05,
+			probe_distance: 0.2,
+			max_step_speed: 3.0,
+			snap_distance: 0.25,
+			max_snap_speed: 5.0,
+		}
+	}
+}
+
+impl StepSystem {
+	pub fn new(max_step_height: f32, probe_radius: f32, probe_distance: f32) -> Self {
+		debug_assert!(max_step_height >= 0. && probe_radius > 0. && probe_distance >= 0.);
+		Self {
+			max_step_height,
+			probe_radius,
+			probe_distance,
+			snap_distance: max_step_height,
+			..default()
+		}
+	}
+
+	/* returns the height of the step ahead of `position`, relative to the ground below it */
+	pub fn detect_step(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, floating: &FloatingSystem, position: Vec3, direction: Dir3) -> Option<(f32, f32)> {
+		if !self.enable {
+			return None;
+		}
+		let float_height = floating.float_height();
+		let ground = position.y - float_height;
+		let origin = position.with_y(ground + self.probe_radius);
+		let wall = spatial_query.cast_shape(
+			&Collider::sphere(self.probe_radius),
+			origin,
+			Quat::IDENTITY,
+			direction,
+			self.probe_distance,
+			true,
+			filter.clone(),
+		)?;
+		/* walkable slopes are handled by the float spring */
+		if floating.is_walkable(wall.normal1) {
+			return None;
+		}
+		let top_origin = origin + direction * (wall.time_of_impact + self.probe_radius * 2.);
+		let top_origin = top_origin.with_y(ground + self.max_step_height + float_height);
+		let top = spatial_query.cast_ray(
+			top_origin,
+			Dir3::NEG_Y,
+			self.max_step_height + float_height,
+			true,
+			filter,
+		)?;
+		if !floating.is_walkable(top.normal) {
+			return None;
+		}
+		let step_height = top_origin.y - top.time_of_impact - ground;
+		if step_height > 0. && step_height <= self.max_step_height {
+			Some((step_height, wall.time_of_impact))
+		} else {
+			None
+		}
+	}
+
+	/* vertical velocity needed to clear a step of `step_height` at `distance` while moving at `speed` */
+	pub fn step_velocity(&self, step_height: f32, distance: f32, speed: f32) -> f32 {
+		let time = distance.max(self.probe_radius) / speed.max(f32::EPSILON);
+		(step_height / time).min(self.max_step_speed)
+	}
+
+	pub fn cast_snap_ray(&self, spatial_query: &SpatialQuery, filter: SpatialQueryFilter, position: Vec3, float_range: f32) -> Option<RayHitData> {
+		if !self.enable {
+			return None;
+		}
+		spatial_query.cast_ray(
+			position,
+			Dir3::NEG_Y,
+			float_range + self.snap_distance,
+			true,
+			filter,
+		)
+	}
+
+	/* downward velocity pulling the character back into float range within one frame */
+	pub fn snap_velocity(&self, gap: f32, delta_seconds: f32) -> f32 {
+		-(gap / delta_seconds.max(f32::EPSILON)).min(self.max_snap_speed)
+	}
+}
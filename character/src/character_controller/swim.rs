@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use physics::buoyancy::BuoyancySystem;
+
+use world::WaterVolume;
+
+#[derive(Component)]
+pub struct SwimSystem {
+	pub enable: bool,
+	pub body_height: f32,
+	pub body_density: f32,
+	/* submerged fraction at which the character starts swimming */
+	pub swim_depth: f32,
+	pub dive_acceleration: f32,
+	pub buoyancy: BuoyancySystem,
+	water: Option<WaterVolume>,
+	submerged: f32,
+	dive: f32,
+}
+
+impl Default for SwimSystem {
+	fn default() -> Self {
+		Self {
+			enable: true,
+			body_height: 1.5,
+			body_density: 0.9,
+			swim_depth: 0.6,
+			dive_acceleration: 6.0,
+			buoyancy: BuoyancySystem::default(),
+			water: None,
+			submerged: 0.,
+			dive: 0.,
+		}
+	}
+}
+
+impl SwimSystem {
+	pub fn new(body_height: f32, body_density: f32, swim_depth: f32) -> Self {
+		debug_assert!(body_height > 0. && body_density > 0. && (0.0..=1.0).contains(&swim_depth));
+		Self {
+			body_height,
+			body_density,
+			swim_depth,
+			..default()
+		}
+	}
+	pub fn water(&self) -> Option<WaterVolume> {
+		self.water
+	}
+	pub fn submerged(&self) -> f32 {
+		self.submerged
+	}
+	pub fn should_swim(&self) -> bool {
+		self.enable && self.submerged >= self.swim_depth
+	}
+	/* positive ascends, negative dives */
+	pub fn dive(&mut self, dive: f32) {
+		self.dive = dive.clamp(-1., 1.);
+	}
+	pub(super) fn update(&mut self, water: Option<WaterVolume>, feet: f32, surface: f32, mass: f32) {
+		self.water = water;
+		self.submerged = match water {
+			Some(water) => {
+				self.buoyancy.fluid_density = water.density;
+				self.buoyancy.linear_drag = water.drag;
+				((surface - feet) / self.body_height).clamp(0., 1.)
+			},
+			None => 0.,
+		};
+		self.buoyancy.displaced_volume = mass / self.body_density;
+	}
+	pub fn compute_force(&self, mass: f32, gravity: Vec3, velocity: Vec3) -> Vec3 {
+		let buoyancy = Vec3::Y * self.buoyancy.compute_force(gravity.length(), self.submerged);
+		let drag = Vec3::new(
+			self.buoyancy.compute_drag(velocity.x, self.submerged),
+			self.buoyancy.compute_drag(velocity.y, self.submerged),
+			self.buoyancy.compute_drag(velocity.z, self.submerged),
+		) * mass;
+		let dive = Vec3::Y * self.dive * self.dive_acceleration * mass;
+		buoyancy + drag + dive
+	}
+}
@@ -4,8 +4,30 @@ mod character_controller;
 #[allow(unused_imports)]
 pub use character_controller::{
 	CharacterControllerPlugin,
+	SlopePlugin,
+	PlatformPlugin,
 	CharacterController,
-	CharacterState
+	CharacterState,
+	GroundedState,
+	AirborneState,
+	ClimbingState,
+	StateChanged,
+	StateHooks,
+	StateMachine,
+	StunCharacter,
+	MovementParameters,
+	JumpSystem,
+	Stance,
+	StanceSystem,
+	StanceCollider,
+	StanceColliderBundle,
+	Ledge,
+	LedgeInput,
+	LedgeSystem,
+	SwimSystem,
+	GroundProbe,
+	GroundHit,
+	GroundInfo
 };
 
 pub use actions::*;
@@ -47,6 +69,7 @@ fn load_character<C>(
 pub struct CharacterBundle<C: 'static + Sync + Send + Component + Character> {
 	pub character: C,
 	controller: CharacterController,
+	ground: GroundInfo,
 	mass_properties_bundle: MassPropertiesBundle,
 	pub rigid_body: RigidBody,
 	pub locked_axes: LockedAxes,
@@ -55,8 +78,14 @@ pub struct CharacterBundle<C: 'static + Sync + Send + Component + Character> {
 impl<C: 'static + Sync + Send + Component + Character> CharacterBundle<C> {
 	pub fn new(character: C) -> Self {
 		Self {
+			controller: CharacterController::new(Mass::default())
+				.with_jump(character.jump_system())
+				.with_stance(character.stance_system())
+				.with_ledge(character.ledge_system())
+				.with_swim(character.swim_system())
+				.with_ground_probe(character.ground_probe()),
 			character,
-			controller: CharacterController::new(Mass::default()),
+			ground: GroundInfo::default(),
 			mass_properties_bundle: MassPropertiesBundle::default(),
 			rigid_body: RigidBody::Dynamic,
 			locked_axes: LockedAxes::ROTATION_LOCKED,
@@ -70,6 +99,21 @@ pub trait Character {
 	fn animations(asset_server: &AssetServer) -> CharacterActionClips;
 	fn collider_constructor_hierarchy() -> ColliderConstructorHierarchy;
 	fn mass_properties(&self) -> MassPropertiesBundle;
+	fn jump_system(&self) -> JumpSystem {
+		JumpSystem::default()
+	}
+	fn stance_system(&self) -> StanceSystem {
+		StanceSystem::default()
+	}
+	fn ledge_system(&self) -> LedgeSystem {
+		LedgeSystem::default()
+	}
+	fn swim_system(&self) -> SwimSystem {
+		SwimSystem::default()
+	}
+	fn ground_probe(&self) -> GroundProbe {
+		GroundProbe::default()
+	}
 }
 
 #[derive(Component, Deref, Copy, Clone)]
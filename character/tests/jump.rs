@@ -0,0 +1,144 @@
+use character::*;
+
+const DELTA: f32 = 1. / 60.;
+
+/* one frame of the controller: read the button, tick the timers, then try to jump */
+fn frame(jump: &mut JumpSystem, held: bool, grounded: bool) -> bool {
+	jump.press(held);
+	jump.update(grounded, DELTA);
+	jump.try_jump(grounded)
+}
+
+fn jump_system() -> JumpSystem {
+	JumpSystem::new(0.5, 0, 0.1, 0.15)
+}
+
+#[test]
+fn buffered_press_jumps_on_landing() {
+	let mut jump = jump_system();
+	assert!(!frame(&mut jump, true, false));
+	/* landing a few frames later, still inside the buffer */
+	for _ in 0..3 {
+		assert!(!frame(&mut jump, true, false));
+	}
+	assert!(frame(&mut jump, true, true));
+}
+
+#[test]
+fn buffer_expires() {
+	let mut jump = jump_system();
+	assert!(!frame(&mut jump, true, false));
+	/* 0.2 s in the air outlasts the 0.15 s buffer */
+	for _ in 0..12 {
+		frame(&mut jump, true, false);
+	}
+	assert!(!frame(&mut jump, true, true));
+}
+
+#[test]
+fn holding_jump_does_not_repeat() {
+	let mut jump = jump_system();
+	assert!(frame(&mut jump, true, true));
+	for _ in 0..30 {
+		assert!(!frame(&mut jump, true, true));
+	}
+}
+
+#[test]
+fn coyote_time_allows_a_late_jump() {
+	let mut jump = jump_system();
+	frame(&mut jump, false, true);
+	/* walked off the ledge three frames ago */
+	for _ in 0..3 {
+		frame(&mut jump, false, false);
+	}
+	assert!(frame(&mut jump, true, false));
+}
+
+#[test]
+fn coyote_time_expires() {
+	let mut jump = jump_system();
+	frame(&mut jump, false, true);
+	/* 0.15 s off the ledge outlasts the 0.1 s coyote time */
+	for _ in 0..9 {
+		frame(&mut jump, false, false);
+	}
+	assert!(!frame(&mut jump, true, false));
+}
+
+#[test]
+fn coyote_jump_is_used_once() {
+	let mut jump = jump_system();
+	frame(&mut jump, false, true);
+	frame(&mut jump, false, false);
+	assert!(frame(&mut jump, true, false));
+	frame(&mut jump, false, false);
+	assert!(!frame(&mut jump, true, false));
+}
+
+#[test]
+fn air_jumps_are_counted() {
+	let mut jump = JumpSystem::new(0.5, 2, 0., 0.15);
+	frame(&mut jump, false, true);
+	frame(&mut jump, false, false);
+	for _ in 0..2 {
+		assert!(frame(&mut jump, true, false));
+		frame(&mut jump, false, false);
+	}
+	assert!(!frame(&mut jump, true, false));
+}
+
+#[test]
+fn landing_restores_air_jumps() {
+	let mut jump = JumpSystem::new(0.5, 1, 0., 0.15);
+	frame(&mut jump, false, true);
+	frame(&mut jump, false, false);
+	assert!(frame(&mut jump, true, false));
+	frame(&mut jump, false, true);
+	frame(&mut jump, false, false);
+	assert!(frame(&mut jump, true, false));
+}
+
+#[test]
+fn releasing_early_cuts_the_jump() {
+	let mut jump = jump_system().with_release_dampening(0.5);
+	assert!(frame(&mut jump, true, true));
+	assert_eq!(jump.cut(3.), None);
+	jump.press(false);
+	assert_eq!(jump.cut(3.), Some(1.5));
+	/* the cut only happens once */
+	assert_eq!(jump.cut(1.5), None);
+	assert!(!jump.is_rising());
+}
+
+#[test]
+fn tapped_jump_is_cut_on_the_first_frame() {
+	let mut jump = jump_system().with_release_dampening(0.5);
+	jump.press(true);
+	jump.press(false);
+	jump.update(true, DELTA);
+	assert!(jump.try_jump(true));
+	assert_eq!(jump.cut(4.), Some(2.));
+}
+
+#[test]
+fn releasing_after_the_apex_keeps_the_fall() {
+	let mut jump = jump_system().with_release_dampening(0.5);
+	assert!(frame(&mut jump, true, true));
+	assert_eq!(jump.cut(-0.1), None);
+	jump.press(false);
+	assert_eq!(jump.cut(-1.), None);
+}
+
+#[test]
+fn impulse_reaches_the_jump_height() {
+	let jump = JumpSystem::new(2., 0, 0.1, 0.15);
+	let gravity = 9.81;
+	let velocity = f32::sqrt(2. * gravity * 2.);
+	assert!((jump.impulse(1., gravity, 0.).y - velocity).abs() < 1e-4);
+	assert!((jump.impulse(3., gravity, 0.).y - 3. * velocity).abs() < 1e-4);
+	/* existing upward velocity is topped up, not added to */
+	assert!((jump.impulse(1., gravity, 2.).y - (velocity - 2.)).abs() < 1e-4);
+	assert_eq!(jump.impulse(1., gravity, 10.).y, 0.);
+	assert_eq!(jump.impulse(1., gravity, 0.).x, 0.);
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use bevy::{
+	prelude::*,
+	time::TimeUpdateStrategy,
+};
+use avian3d::prelude::*;
+
+use character::*;
+
+const TIMESTEP: f32 = 1. / 60.;
+
+fn spawn_platform(app: &mut App, velocity: Vec3, angular_velocity: Vec3) -> Entity {
+	app.world_mut().spawn((
+		LinearVelocity(velocity),
+		AngularVelocity(angular_velocity),
+		Position(Vec3::ZERO),
+		Rotation::default(),
+		CenterOfMass::default(),
+	)).id()
+}
+
+/* the character stands `offset` away from the platform's centre */
+fn platform_app(velocity: Vec3, angular_velocity: Vec3, offset: Vec3) -> (App, Entity, Entity) {
+	let mut app = App::new();
+	app.add_plugins((MinimalPlugins, PlatformPlugin))
+		.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(TIMESTEP)));
+	/* the first frame has no delta */
+	app.update();
+	let platform = spawn_platform(&mut app, velocity, angular_velocity);
+	let character = app.world_mut().spawn((
+		CharacterController::new(Mass(1.)),
+		GroundInfo(Some(GroundHit {
+			entity: platform,
+			point: offset,
+			normal: Vec3::Y,
+			distance: 0.095,
+		})),
+		Transform::from_translation(offset),
+		LinearVelocity::default(),
+	)).id();
+	app.update();
+	(app, platform, character)
+}
+
+fn velocity(app: &App, entity: Entity) -> Vec3 {
+	app.world().get::<LinearVelocity>(entity).unwrap().0
+}
+
+fn leave_platform(app: &mut App, character: Entity) {
+	app.world_mut().entity_mut(character).insert(GroundInfo(None));
+	app.update();
+}
+
+#[test]
+fn moving_platform_carries_the_character() {
+	let (mut app, platform, character) = platform_app(Vec3::new(2., 0., 1.), Vec3::ZERO, Vec3::ZERO);
+	assert!((velocity(&app, character) - Vec3::new(2., 0., 1.)).length() < 1e-4);
+	/* the platform velocity is only added once */
+	app.update();
+	assert!((velocity(&app, character) - Vec3::new(2., 0., 1.)).length() < 1e-4);
+	let controller = app.world().get::<CharacterController>(character).unwrap();
+	assert_eq!(controller.platform.platform(), Some(platform));
+}
+
+#[test]
+fn platform_speed_changes_are_followed() {
+	let (mut app, platform, character) = platform_app(Vec3::X, Vec3::ZERO, Vec3::ZERO);
+	app.world_mut().get_mut::<LinearVelocity>(platform).unwrap().0 = Vec3::X * 3.;
+	app.update();
+	assert!((velocity(&app, character) - Vec3::X * 3.).length() < 1e-4);
+}
+
+#[test]
+fn rotating_platform_carries_and_turns_the_character() {
+	let (app, _, character) = platform_app(Vec3::ZERO, Vec3::Y, Vec3::X);
+	/* the edge of a platform spinning counter clockwise moves towards -z */
+	assert!((velocity(&app, character) - Vec3::NEG_Z).length() < 1e-4);
+	let rotation = app.world().get::<Transform>(character).unwrap().rotation;
+	let (_, yaw, _) = rotation.to_euler(EulerRot::XYZ);
+	assert!((yaw - TIMESTEP).abs() < 1e-4);
+}
+
+#[test]
+fn leaving_keeps_the_platform_velocity() {
+	let (mut app, _, character) = platform_app(Vec3::new(2., 0., 1.), Vec3::ZERO, Vec3::ZERO);
+	leave_platform(&mut app, character);
+	assert!((velocity(&app, character) - Vec3::new(2., 0., 1.)).length() < 1e-4);
+	let controller = app.world().get::<CharacterController>(character).unwrap();
+	assert_eq!(controller.platform.platform(), None);
+	assert_eq!(controller.platform.velocity(), Vec3::ZERO);
+}
+
+#[test]
+fn jumping_off_keeps_the_platform_velocity() {
+	let (mut app, _, character) = platform_app(Vec3::ZERO, Vec3::Y, Vec3::X);
+	app.world_mut().get_mut::<CharacterController>(character).unwrap()
+		.set_state(CharacterState::Airborne(AirborneState::Rising));
+	app.update();
+	assert!((velocity(&app, character) - Vec3::NEG_Z).length() < 1e-4);
+	let controller = app.world().get::<CharacterController>(character).unwrap();
+	assert_eq!(controller.platform.platform(), None);
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use avian3d::prelude::*;
+
+use character::*;
+
+/* normal of a slope descending towards +x */
+fn slope_normal(angle: f32) -> Vec3 {
+	let angle = f32::to_radians(angle);
+	Vec3::new(angle.sin(), angle.cos(), 0.)
+}
+
+fn script_ground(app: &mut App, entity: Entity, angle: f32) {
+	app.world_mut().entity_mut(entity).insert(GroundInfo(Some(GroundHit {
+		entity: Entity::PLACEHOLDER,
+		point: Vec3::ZERO,
+		normal: slope_normal(angle),
+		distance: 0.095,
+	})));
+	app.update();
+}
+
+fn slope_app(angle: f32) -> (App, Entity) {
+	let mut app = App::new();
+	app.add_plugins((MinimalPlugins, SlopePlugin))
+		.insert_resource(Gravity::default());
+	let entity = app.world_mut().spawn((
+		CharacterController::new(Mass(1.)),
+		GroundInfo::default(),
+		Mass(1.),
+		ExternalForce::default(),
+	)).id();
+	script_ground(&mut app, entity, angle);
+	(app, entity)
+}
+
+fn state(app: &App, entity: Entity) -> CharacterState {
+	app.world().get::<CharacterController>(entity).unwrap().state.current()
+}
+
+#[test]
+fn gentle_slope_is_walkable() {
+	let (app, entity) = slope_app(15.);
+	assert_eq!(state(&app, entity), CharacterState::Grounded(GroundedState::Standing));
+	assert_eq!(app.world().get::<ExternalForce>(entity).unwrap().force(), Vec3::ZERO);
+}
+
+#[test]
+fn steep_slope_slides_downhill() {
+	let (app, entity) = slope_app(70.);
+	assert_eq!(state(&app, entity), CharacterState::Grounded(GroundedState::Sliding));
+	let force = app.world().get::<ExternalForce>(entity).unwrap().force();
+	assert!(force.x > 0. && force.y < 0.);
+	assert!(force.dot(slope_normal(70.)).abs() < 1e-4);
+}
+
+#[test]
+fn sliding_stops_on_walkable_ground() {
+	let (mut app, entity) = slope_app(70.);
+	script_ground(&mut app, entity, 5.);
+	assert_eq!(state(&app, entity), CharacterState::Grounded(GroundedState::Standing));
+}
+
+#[test]
+fn movement_follows_the_ground_plane() {
+	let (app, entity) = slope_app(20.);
+	let controller = app.world().get::<CharacterController>(entity).unwrap();
+	let (uphill, uphill_speed) = controller.movement.project_on_ground(Dir3::NEG_X);
+	let (downhill, downhill_speed) = controller.movement.project_on_ground(Dir3::X);
+	let (across, across_speed) = controller.movement.project_on_ground(Dir3::Z);
+	assert!(uphill.y > 0. && downhill.y < 0.);
+	assert!(uphill.dot(slope_normal(20.)).abs() < 1e-4);
+	assert!(uphill_speed < 1. && downhill_speed > 1.);
+	assert!(across.y.abs() < 1e-4 && (across_speed - 1.).abs() < 1e-4);
+}
+
+#[test]
+fn steeper_slopes_are_slower_uphill() {
+	let (gentle_app, gentle) = slope_app(10.);
+	let (steep_app, steep) = slope_app(40.);
+	let (_, gentle_speed) = gentle_app.world().get::<CharacterController>(gentle).unwrap()
+		.movement.project_on_ground(Dir3::NEG_X);
+	let (_, steep_speed) = steep_app.world().get::<CharacterController>(steep).unwrap()
+		.movement.project_on_ground(Dir3::NEG_X);
+	assert!(steep_speed < gentle_speed);
+}
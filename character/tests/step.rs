@@ -0,0 +1,59 @@
+use bevy::{
+	prelude::*,
+	ecs::system::RunSystemOnce,
+};
+use avian3d::prelude::*;
+
+use character::*;
+use world::SpatialTypes;
+
+/* a box of `height` standing on the ground at y = 0, its near face 0.15 m ahead along +x */
+fn step_world(height: f32) -> World {
+	let mut world = World::new();
+	world.insert_resource(SpatialQueryPipeline::default());
+	world.spawn((
+		RigidBody::Static,
+		Collider::cuboid(1., height, 2.),
+		Position(Vec3::new(0.65, height / 2., 0.)),
+		Rotation::default(),
+		CollisionLayers::new(SpatialTypes::World, LayerMask::ALL),
+	));
+	world
+}
+
+/* the character floats above the ground at the origin, walking towards the step */
+fn detect_step(world: &mut World) -> Option<(f32, f32)> {
+	world.run_system_once(|mut spatial_query: SpatialQuery| {
+		let controller = CharacterController::new(Mass(1.));
+		spatial_query.update_pipeline();
+		let position = Vec3::Y * controller.floating.float_height();
+		let filter = SpatialQueryFilter::from_mask(SpatialTypes::World);
+		controller.step.detect_step(&spatial_query, filter, &controller.floating, position, Dir3::X)
+	})
+}
+
+#[test]
+fn low_step_is_climbed() {
+	let mut world = step_world(0.2);
+	let (step_height, distance) = detect_step(&mut world).expect("step should be detected");
+	assert!((step_height - 0.2).abs() < 1e-3);
+	assert!((distance - 0.1).abs() < 1e-3);
+	let step = CharacterController::new(Mass(1.)).step;
+	let y_velocity = step.step_velocity(step_height, distance, 2.);
+	assert!(y_velocity > 0. && y_velocity <= step.max_step_speed);
+}
+
+#[test]
+fn step_above_max_height_is_rejected() {
+	let step = CharacterController::new(Mass(1.)).step;
+	let mut world = step_world(step.max_step_height + 0.15);
+	assert_eq!(detect_step(&mut world), None);
+}
+
+#[test]
+fn step_velocity_is_capped() {
+	let step = CharacterController::new(Mass(1.)).step;
+	assert_eq!(step.step_velocity(0.2, 0.01, 100.), step.max_step_speed);
+	/* slower approaches rise more gently */
+	assert!(step.step_velocity(0.2, 0.1, 1.) < step.step_velocity(0.2, 0.1, 2.));
+}
@@ -0,0 +1,23 @@
+use bevy::{
+	prelude::*,
+	window::WindowMode,
+};
+
+use okfd::BasePlugin;
+
+/* mobile entry point, desktop builds start from main.rs */
+#[bevy_main]
+fn main() {
+	App::new().add_plugins((
+		DefaultPlugins.set(WindowPlugin {
+			primary_window: Some(Window {
+				mode: WindowMode::BorderlessFullscreen,
+				resizable: false,
+				..default()
+			}),
+			..default()
+		}),
+		avian3d::PhysicsPlugins::default(),
+		BasePlugin,
+	)).run();
+}
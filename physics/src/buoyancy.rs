@@ -0,0 +1,17 @@
+
+/* forces are along the gravity axis, scaled by the submerged fraction of the body */
+#[derive(Default)]
+pub struct BuoyancySystem {
+	pub fluid_density: f32,
+	pub displaced_volume: f32,
+	pub linear_drag: f32,
+}
+
+impl BuoyancySystem {
+	pub fn compute_force(&self, gravity: f32, submerged: f32) -> f32 {
+		self.fluid_density * self.displaced_volume * submerged.clamp(0., 1.) * gravity
+	}
+	pub fn compute_drag(&self, v: f32, submerged: f32) -> f32 {
+		-self.linear_drag * submerged.clamp(0., 1.) * v
+	}
+}
@@ -1 +1,2 @@
 pub mod spring;
+pub mod buoyancy;
@@ -1,4 +1,11 @@
-use super::Controlling;
+use super::{
+	Controlling,
+	PlayerAction,
+	PlayerCamera,
+	SplitCamera,
+	CameraMode,
+	LockOnTarget
+};
 
 use bevy::prelude::*;
 use avian3d::prelude::*;
@@ -38,67 +45,128 @@ pub(super) fn init_character_animation_player(
 }
 
 pub(super) fn process_actions(
-	mut action_events: EventReader<CharacterAction>,
-	mut controlling_query: Query<(&mut CharacterController, &ChildAnimationPlayer, &mut ExternalImpulse, &mut LinearVelocity), With<Controlling>>,
-	camera_query: Query<&Transform, With<Camera>>,
+	mut action_events: EventReader<PlayerAction>,
+	mut controlling_query: Query<(Entity, &mut CharacterController, &ChildAnimationPlayer, &mut ExternalImpulse, &mut LinearVelocity, &Controlling)>,
+	camera_query: Query<(&Transform, Option<&PlayerCamera>, Has<SplitCamera>, Option<&LockOnTarget>), With<Camera>>,
+	transform_query: Query<&GlobalTransform>,
+	camera_state: Res<State<CameraMode>>,
 	mut animation_query: Query<(&mut AnimationPlayer, &CharacterActionAnimations)>,
 	time: Res<Time>,
 ) {
-	if action_events.len() == 0 {
-		for (mut controller, &player, _, mut velocity) in controlling_query.iter_mut() {
+	let player_actions: Vec<PlayerAction> = action_events.read().copied().collect();
+	for (entity, mut controller, &player, mut impulse, mut velocity, &Controlling(controller_player)) in controlling_query.iter_mut() {
+		/* movement is relative to the player's own camera */
+		let Some((camera_transform, _, split, lock_on_target)) = camera_query.iter()
+			.find(|(_, camera, _, _)| camera.is_some_and(|camera| camera.0 == controller_player))
+			.or(camera_query.iter().next())
+		else {
+			continue;
+		};
+		/* locked on characters strafe around their target instead */
+		let lock_on_forward = LockOnTarget::of_camera(camera_state.get(), split, lock_on_target)
+			.filter(|lock| lock.character == entity)
+			.and_then(|lock| transform_query.get(lock.target).ok())
+			.zip(transform_query.get(entity).ok())
+			.and_then(|(target, character)| (target.translation() - character.translation()).with_y(0.).try_normalize());
+		let (forward, right) = match lock_on_forward {
+			Some(forward) => (forward, forward.cross(Vec3::Y)),
+			None => (camera_transform.forward().as_vec3(), camera_transform.right().as_vec3()),
+		};
+		let actions: Vec<&PlayerAction> = player_actions.iter()
+			.filter(|event| event.player == controller_player)
+			.collect();
+		if actions.is_empty() {
 			controller.movement.reset_velocity();
-			match controller.state {
-				CharacterState::Able =>	controller.movement.stop_horizontal(&mut (*velocity), time.delta_seconds()),
-				_ => (),
+			if controller.is_grounded() {
+				controller.stop_horizontal(&mut velocity, time.delta_seconds());
 			}
 			if let Ok((player, animated_actions)) = animation_query.get_mut(*player) {
 				play_action_animation(player, animated_actions, &CharacterAction::Idle);
 			}
 		}
-	}
-	for ((mut controller, &player, mut impulse, mut velocity), camera_transform) in controlling_query.iter_mut().zip(camera_query.iter()) {
 		let mut direction = Vec3::ZERO;
 		let mut sprint = false;
-		for action in action_events.read() {
-			if let Ok((player, animated_actions)) = animation_query.get_mut(*player) {
-				play_action_animation(player, animated_actions, action);
+		let mut jump = false;
+		let mut stance = Stance::Standing;
+		let mut climb = false;
+		let mut shimmy = 0.;
+		let mut analog = None;
+		for &&PlayerAction { action, move_axis, .. } in actions.iter() {
+			/* stance animations follow the state machine, see animate_state_changes */
+			if !matches!(action, CharacterAction::Crouch | CharacterAction::Prone) {
+				if let Ok((player, animated_actions)) = animation_query.get_mut(*player) {
+					play_action_animation(player, animated_actions, &action);
+				}
 			}
 			match action {
-				CharacterAction::Forward => direction += camera_transform.forward().as_vec3(),
-				CharacterAction::Backward => direction += camera_transform.back().as_vec3(),
-				CharacterAction::Left => direction += camera_transform.left().as_vec3(),
-				CharacterAction::Right => direction += camera_transform.right().as_vec3(),
+				CharacterAction::Forward => {
+					direction += forward;
+					climb = true;
+				},
+				CharacterAction::Backward => direction -= forward,
+				CharacterAction::Left => {
+					direction -= right;
+					shimmy -= 1.;
+				},
+				CharacterAction::Right => {
+					direction += right;
+					shimmy += 1.;
+				},
 				CharacterAction::Sprint => {
-					match controller.state {
-						CharacterState::Able => sprint = true,
+					match controller.state.current() {
+						CharacterState::Grounded(GroundedState::Standing) => sprint = true,
 						_ => ()
 					}
 				},
-				CharacterAction::Jump => {
-					match controller.state {
-						CharacterState::Able => {
-							controller.state = CharacterState::AirBorne;
-							controller.floating.enable = false;
-							impulse.persistent = false;
-							impulse.apply_impulse(Vec3::Y);
-						},
-						_ => ()
-					}
-				}
+				CharacterAction::Move => if let Some(value) = move_axis {
+					direction += forward.with_y(0.).normalize_or_zero() * value.y;
+					direction += right * value.x;
+					climb |= value.y > 0.5;
+					shimmy += value.x;
+					analog = Some(value.length());
+				},
+				CharacterAction::Jump => jump = true,
+				CharacterAction::Crouch if stance == Stance::Standing => stance = Stance::Crouching,
+				CharacterAction::Prone => stance = Stance::Prone,
 				_ => (),
 			}
 		}
+		controller.jump.press(jump);
+		controller.stance.request(stance);
+		if let CharacterState::Swimming = controller.state.current() {
+			controller.swim.dive(if jump {
+				1.
+			} else if stance != Stance::Standing {
+				-1.
+			} else {
+				0.
+			});
+		}
+		if controller.state.current().is_climbing() {
+			controller.ledge.input(if stance != Stance::Standing {
+				LedgeInput::Drop
+			} else if climb || jump {
+				LedgeInput::Mantle
+			} else if shimmy != 0. {
+				LedgeInput::Shimmy(shimmy)
+			} else {
+				LedgeInput::Hold
+			});
+			continue;
+		}
+		let relative_velocity = controller.platform.relative_velocity(**velocity);
 		impulse.persistent = false;
 		if sprint {
-			impulse.apply_impulse(controller.movement.update_sprint_impulse_horizontal(**velocity, direction));
+			impulse.apply_impulse(controller.movement.update_sprint_impulse_horizontal(relative_velocity, direction));
+		} else if let Some(magnitude) = analog {
+			impulse.apply_impulse(controller.movement.update_analog_impulse_horizontal(relative_velocity, direction, magnitude));
 		} else {
-			impulse.apply_impulse(controller.movement.update_run_impulse_horizontal(**velocity, direction));
+			impulse.apply_impulse(controller.movement.update_run_impulse_horizontal(relative_velocity, direction));
 		}
 
 		if direction == Vec3::ZERO {
-			match controller.state {
-				CharacterState::Able =>	controller.movement.stop_horizontal(&mut (*velocity), time.delta_seconds()),
-				_ => (),
+			if controller.is_grounded() {
+				controller.stop_horizontal(&mut velocity, time.delta_seconds());
 			}
 			if let Ok((player, animated_actions)) = animation_query.get_mut(*player) {
 				play_action_animation(player, animated_actions, &CharacterAction::Idle);
@@ -106,3 +174,63 @@ pub(super) fn process_actions(
 		}
 	}
 }
+
+/* characters nobody controls come to a stop */
+pub(super) fn release_uncontrolled(
+	mut released: RemovedComponents<Controlling>,
+	mut character_query: Query<(&mut CharacterController, &ChildAnimationPlayer, &mut LinearVelocity), Without<Controlling>>,
+	mut animation_query: Query<(&mut AnimationPlayer, &CharacterActionAnimations)>,
+	time: Res<Time>,
+) {
+	for entity in released.read() {
+		let Ok((mut controller, &player, _)) = character_query.get_mut(entity) else {
+			continue;
+		};
+		controller.jump.press(false);
+		controller.stance.request(Stance::Standing);
+		controller.ledge.input(LedgeInput::Hold);
+		controller.swim.dive(0.);
+		if let Ok((player, animated_actions)) = animation_query.get_mut(*player) {
+			play_action_animation(player, animated_actions, &CharacterAction::Idle);
+		}
+	}
+	for (mut controller, _, mut velocity) in character_query.iter_mut() {
+		controller.movement.reset_velocity();
+		if controller.is_grounded() {
+			controller.stop_horizontal(&mut velocity, time.delta_seconds());
+		}
+	}
+}
+
+pub(super) fn animate_state_changes(
+	mut state_events: EventReader<StateChanged>,
+	player_query: Query<&ChildAnimationPlayer>,
+	mut animation_query: Query<(&mut AnimationPlayer, &CharacterActionAnimations)>,
+) {
+	for event in state_events.read() {
+		let Ok(&player) = player_query.get(event.entity) else {
+			continue;
+		};
+		let Ok((mut player, animated_actions)) = animation_query.get_mut(*player) else {
+			continue;
+		};
+		/* characters without stance clips stand idle instead of silently playing nothing */
+		let stance_action = |action: CharacterAction| if animated_actions.0.contains_key(&action) {
+			action
+		} else {
+			CharacterAction::Idle
+		};
+		match event.to {
+			CharacterState::Grounded(GroundedState::Crouching) => {
+				play_action_animation(player, animated_actions, &stance_action(CharacterAction::Crouch));
+			},
+			CharacterState::Grounded(GroundedState::Prone) => {
+				play_action_animation(player, animated_actions, &stance_action(CharacterAction::Prone));
+			},
+			_ => {
+				stop_action_animation(player.as_mut(), animated_actions, CharacterAction::Crouch);
+				stop_action_animation(player.as_mut(), animated_actions, CharacterAction::Prone);
+			},
+		}
+	}
+}
@@ -1,6 +1,28 @@
+use super::{
+	player::{
+		Player,
+		PlayerAction,
+		GamepadAssignments
+	},
+	context::{
+		InputContext,
+		InputContextStack
+	},
+	possession::Hotkey,
+	trigger::*,
+};
+
 use bevy::{
 	prelude::*,
-	input::ButtonInput,
+	ecs::system::SystemParam,
+	input::{
+		ButtonInput,
+		gamepad::{
+			GamepadAxis,
+			GamepadAxisType,
+			GamepadButtonType
+		}
+	},
 	utils::HashMap,
 };
 use serde::{
@@ -8,75 +30,333 @@ use serde::{
 	Deserialize
 };
 use bevy_persistent::prelude::*;
+use bevy_egui::EguiContexts;
 
 use character::CharacterAction;
 
+/* the top level tables of the bindings file belong to the gameplay context */
 #[derive(Resource, Serialize, Deserialize, Default)]
 pub struct Bindings {
-	pub keys: HashMap<KeyCode, CharacterAction>,
-	pub mouse: HashMap<MouseButton, CharacterAction>
+	#[serde(flatten)]
+	pub gameplay: BindingTable,
+	#[serde(default)]
+	pub contexts: HashMap<InputContext, BindingTable>,
+	#[serde(default)]
+	pub hotkeys: HashMap<KeyCode, Hotkey>,
+	#[serde(default = "PlayerProfile::defaults")]
+	pub players: Vec<PlayerProfile>,
+	#[serde(default)]
+	pub analog: AnalogSettings,
 }
 
-pub(super) fn process_input(
-	key_input: Res<ButtonInput<KeyCode>>,
-	mouse_input: Res<ButtonInput<MouseButton>>,
-	bindings: Res<Persistent<Bindings>>,
-	mut action_events: EventWriter<CharacterAction>
-) {
-	let mut actions: Vec<CharacterAction> = Vec::new();
-
-	/* pressed */
-	for key in key_input.get_pressed() {
-		if let Some(&action) = bindings.get().keys.get(key) {
-			match action {
-				CharacterAction::Forward | CharacterAction::Backward => actions.push(action),
-				CharacterAction::Left | CharacterAction::Right => actions.push(action),
-				CharacterAction::Sprint => actions.push(action),
-				_ => (),
-			}
+impl Bindings {
+	pub fn table(&self, context: InputContext) -> Option<&BindingTable> {
+		match context {
+			InputContext::Gameplay => Some(&self.gameplay),
+			context => self.contexts.get(&context),
 		}
 	}
-	for button in mouse_input.get_pressed() {
-		if let Some(&action) = bindings.get().mouse.get(button) {
-			match action {
-				_ => (),
-			}
+	/* menus share one table, gameplay can be overridden per player */
+	pub fn player_table(&self, player: Player, context: InputContext) -> Option<&BindingTable> {
+		match (context, self.players.get(player.0)) {
+			(InputContext::Gameplay, Some(PlayerProfile { bindings: Some(table), .. })) => Some(table),
+			(context, _) => self.table(context),
+		}
+	}
+	/* creates the table on first edit, a player's copy starts out as the shared gameplay table */
+	pub fn player_table_mut(&mut self, player: Option<Player>, context: InputContext) -> &mut BindingTable {
+		match (context, player) {
+			(InputContext::Gameplay, Some(player)) if player.0 < self.players.len() => {
+				let gameplay = &self.gameplay;
+				self.players[player.0].bindings.get_or_insert_with(|| gameplay.clone())
+			},
+			(InputContext::Gameplay, _) => &mut self.gameplay,
+			(context, _) => self.contexts.entry(context).or_default(),
 		}
 	}
+}
 
-	/* just pressed */
-	for key in key_input.get_just_pressed() {
-		if let Some(&action) = bindings.get().keys.get(key) {
-			match action {
-				CharacterAction::Jump => actions.push(action),
-				_ => (),
-			}
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PlayerProfile {
+	/* keyboard and mouse drive this player */
+	pub keyboard: bool,
+	/* connected gamepads are handed to the first player accepting one */
+	pub gamepad: bool,
+	/* the on screen joystick and buttons drive this player */
+	pub touch: bool,
+	/* replaces the gameplay tables for this player */
+	pub bindings: Option<BindingTable>,
+}
+
+impl PlayerProfile {
+	/* one player on keyboard and mouse, another joining with a gamepad */
+	pub fn defaults() -> Vec<Self> {
+		vec![
+			Self {
+				keyboard: true,
+				touch: true,
+				..default()
+			},
+			Self {
+				gamepad: true,
+				..default()
+			},
+		]
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct BindingTable {
+	pub keys: HashMap<KeyCode, ActionBinding>,
+	pub mouse: HashMap<MouseButton, ActionBinding>,
+	pub gamepad: GamepadBindings,
+}
+
+/* actions that can be bound to buttons, analog movement comes from axes */
+pub const BINDABLE_ACTIONS: [CharacterAction; 8] = [
+	CharacterAction::Forward,
+	CharacterAction::Backward,
+	CharacterAction::Left,
+	CharacterAction::Right,
+	CharacterAction::Sprint,
+	CharacterAction::Jump,
+	CharacterAction::Crouch,
+	CharacterAction::Prone,
+];
+
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum InputSource {
+	Key(KeyCode),
+	Mouse(MouseButton),
+	Gamepad(GamepadButtonType),
+}
+
+impl BindingTable {
+	pub fn binding(&self, source: InputSource) -> Option<ActionBinding> {
+		match source {
+			InputSource::Key(key) => self.keys.get(&key),
+			InputSource::Mouse(button) => self.mouse.get(&button),
+			InputSource::Gamepad(button) => self.gamepad.buttons.get(&button),
+		}.copied()
+	}
+	pub fn action(&self, source: InputSource) -> Option<CharacterAction> {
+		self.binding(source).map(|binding| binding.action)
+	}
+	/* every input bound to the action, in a stable order */
+	pub fn sources(&self, action: CharacterAction) -> Vec<InputSource> {
+		let keys = self.keys.iter()
+			.filter(|(_, bound)| bound.action == action)
+			.map(|(&key, _)| InputSource::Key(key));
+		let mouse = self.mouse.iter()
+			.filter(|(_, bound)| bound.action == action)
+			.map(|(&button, _)| InputSource::Mouse(button));
+		let gamepad = self.gamepad.buttons.iter()
+			.filter(|(_, bound)| bound.action == action)
+			.map(|(&button, _)| InputSource::Gamepad(button));
+		let mut sources: Vec<InputSource> = keys.chain(mouse).chain(gamepad).collect();
+		sources.sort_by_cached_key(|source| format!("{source:?}"));
+		sources
+	}
+	/* returns what the source was previously bound to */
+	pub fn bind(&mut self, source: InputSource, binding: impl Into<ActionBinding>) -> Option<ActionBinding> {
+		let binding = binding.into();
+		match source {
+			InputSource::Key(key) => self.keys.insert(key, binding),
+			InputSource::Mouse(button) => self.mouse.insert(button, binding),
+			InputSource::Gamepad(button) => self.gamepad.buttons.insert(button, binding),
 		}
 	}
-	for button in mouse_input.get_just_pressed() {
-		if let Some(&action) = bindings.get().mouse.get(button) {
-			match action {
-				CharacterAction::Jump => actions.push(action),
-				_ => (),
-			}
+	pub fn unbind(&mut self, source: InputSource) -> Option<ActionBinding> {
+		match source {
+			InputSource::Key(key) => self.keys.remove(&key),
+			InputSource::Mouse(button) => self.mouse.remove(&button),
+			InputSource::Gamepad(button) => self.gamepad.buttons.remove(&button),
 		}
 	}
+}
 
-	/* just released */
-	for key in key_input.get_just_released() {
-		if let Some(&action) = bindings.get().keys.get(key) {
-			match action {
-				_ => (),
-			}
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct GamepadBindings {
+	pub buttons: HashMap<GamepadButtonType, ActionBinding>,
+	pub axes: HashMap<GamepadAxisType, AxisBinding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AxisBinding {
+	/* analog movement, filtered through the analog settings */
+	MoveX,
+	MoveY,
+	/* digital action while the axis is pushed past the deadzone */
+	Positive { action: CharacterAction, deadzone: f32 },
+	Negative { action: CharacterAction, deadzone: f32 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ResponseCurve {
+	Linear,
+	Quadratic,
+	Cubic,
+	Power(f32),
+}
+
+impl ResponseCurve {
+	pub fn apply(&self, t: f32) -> f32 {
+		match *self {
+			ResponseCurve::Linear => t,
+			ResponseCurve::Quadratic => t * t,
+			ResponseCurve::Cubic => t * t * t,
+			ResponseCurve::Power(exponent) => t.powf(exponent),
 		}
 	}
-	for button in mouse_input.get_just_released() {
-		if let Some(&action) = bindings.get().mouse.get(button) {
-			match action {
-				_ => (),
-			}
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct AnalogSettings {
+	pub inner_deadzone: f32,
+	pub outer_deadzone: f32,
+	pub curve: ResponseCurve,
+}
+
+impl Default for AnalogSettings {
+	fn default() -> Self {
+		Self {
+			inner_deadzone: 0.15,
+			outer_deadzone: 0.95,
+			curve: ResponseCurve::Quadratic,
 		}
 	}
+}
+
+impl AnalogSettings {
+	/* radial deadzone, remapping the live zone onto the response curve */
+	pub fn apply(&self, input: Vec2) -> Vec2 {
+		let magnitude = input.length();
+		if magnitude <= self.inner_deadzone {
+			return Vec2::ZERO;
+		}
+		let live_zone = (self.outer_deadzone - self.inner_deadzone).max(f32::EPSILON);
+		let t = ((magnitude - self.inner_deadzone) / live_zone).clamp(0., 1.);
+		input / magnitude * self.curve.apply(t)
+	}
+}
+
+/* the devices bindings are read from */
+#[derive(SystemParam)]
+pub(super) struct InputDevices<'w> {
+	keys: Res<'w, ButtonInput<KeyCode>>,
+	mouse: Res<'w, ButtonInput<MouseButton>>,
+	gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+	gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+	assignments: Res<'w, GamepadAssignments>,
+}
+
+/* one player's active table, along with what its triggers need */
+struct PlayerBindings<'a> {
+	player: Player,
+	table: &'a BindingTable,
+	analog: &'a AnalogSettings,
+	trigger_state: &'a mut TriggerState,
+	time: &'a Time,
+}
+
+impl PlayerBindings<'_> {
+	fn fires(&mut self, gamepad: Option<Gamepad>, source: InputSource, trigger: Trigger, input: TriggerInput) -> bool {
+		self.trigger_state.fires((self.player, gamepad, source), trigger, input, self.time)
+	}
+}
 
+pub(super) fn process_input(
+	devices: InputDevices,
+	bindings: Res<Persistent<Bindings>>,
+	context_stack: Res<InputContextStack>,
+	mut trigger_state: ResMut<TriggerState>,
+	time: Res<Time>,
+	mut contexts: EguiContexts,
+	mut action_events: EventWriter<PlayerAction>
+) {
+	let ctx = contexts.ctx_mut();
+	let (keyboard_focus, pointer_focus) = (ctx.wants_keyboard_input(), ctx.wants_pointer_input());
+	let mut actions: Vec<PlayerAction> = Vec::new();
+	for (index, profile) in bindings.get().players.iter().enumerate() {
+		let player = Player(index);
+		let Some(table) = bindings.get().player_table(player, context_stack.current()) else {
+			continue;
+		};
+		let mut player_bindings = PlayerBindings {
+			player,
+			table,
+			analog: &bindings.get().analog,
+			trigger_state: &mut trigger_state,
+			time: &time,
+		};
+
+		/* gamepads */
+		for gamepad in devices.assignments.gamepads(player) {
+			actions.extend(gamepad_actions(&mut player_bindings, gamepad, &devices));
+		}
+
+		/* keyboard and mouse, unless typing or clicking into egui */
+		if profile.keyboard {
+			for (&key, binding) in table.keys.iter() {
+				let input = if keyboard_focus {
+					TriggerInput::default()
+				} else {
+					TriggerInput::of(&devices.keys, key)
+				};
+				if player_bindings.fires(None, InputSource::Key(key), binding.trigger, input) {
+					actions.push(PlayerAction::new(player, binding.action));
+				}
+			}
+			for (&button, binding) in table.mouse.iter() {
+				let input = if pointer_focus {
+					TriggerInput::default()
+				} else {
+					TriggerInput::of(&devices.mouse, button)
+				};
+				if player_bindings.fires(None, InputSource::Mouse(button), binding.trigger, input) {
+					actions.push(PlayerAction::new(player, binding.action));
+				}
+			}
+		}
+	}
 	action_events.send_batch(actions);
 }
+
+fn gamepad_actions(
+	player_bindings: &mut PlayerBindings,
+	gamepad: Gamepad,
+	devices: &InputDevices,
+) -> Vec<PlayerAction> {
+	let mut actions: Vec<PlayerAction> = Vec::new();
+	let (player, table) = (player_bindings.player, player_bindings.table);
+
+	/* buttons */
+	for (&button_type, binding) in table.gamepad.buttons.iter() {
+		let input = TriggerInput::of(&devices.gamepad_buttons, GamepadButton::new(gamepad, button_type));
+		if player_bindings.fires(Some(gamepad), InputSource::Gamepad(button_type), binding.trigger, input) {
+			actions.push(PlayerAction::new(player, binding.action));
+		}
+	}
+	/* axes */
+	let mut stick = Vec2::ZERO;
+	for (&axis_type, &binding) in table.gamepad.axes.iter() {
+		let value = devices.gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or_default();
+		match binding {
+			AxisBinding::MoveX => stick.x += value,
+			AxisBinding::MoveY => stick.y += value,
+			AxisBinding::Positive { action, deadzone } if value > deadzone => actions.push(PlayerAction::new(player, action)),
+			AxisBinding::Negative { action, deadzone } if value < -deadzone => actions.push(PlayerAction::new(player, action)),
+			_ => (),
+		}
+	}
+	let stick = player_bindings.analog.apply(stick.clamp_length_max(1.));
+	if stick != Vec2::ZERO {
+		actions.push(PlayerAction::movement(player, stick));
+	}
+
+	actions
+}
@@ -0,0 +1,309 @@
+use super::{
+	CameraMode,
+	CameraFallback,
+	super::SplitCamera,
+};
+
+use bevy::{
+	prelude::*,
+	asset::{
+		AssetLoader,
+		AsyncReadExt,
+		LoadContext,
+		LoadState,
+		io::Reader,
+	},
+	utils::ConditionalSendFuture,
+};
+use serde::{
+	Serialize,
+	Deserialize
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+	#[default]
+	Linear,
+	EaseIn,
+	EaseOut,
+	EaseInOut,
+}
+
+impl Easing {
+	pub fn apply(self, t: f32) -> f32 {
+		let t = t.clamp(0., 1.);
+		match self {
+			Self::Linear => t,
+			Self::EaseIn => t * t * t,
+			Self::EaseOut => 1. - (1. - t).powi(3),
+			Self::EaseInOut => t * t * (3. - 2. * t),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum CameraPath {
+	/* passes through every point */
+	CatmullRom(Vec<Vec3>),
+	/* cubic segments of four control points, consecutive segments share their end point */
+	Bezier(Vec<Vec3>),
+}
+
+impl CameraPath {
+	/* position along the whole path, t runs from 0 to 1 */
+	pub fn position(&self, t: f32) -> Vec3 {
+		match self {
+			Self::CatmullRom(points) => {
+				let Some(&last) = points.last() else {
+					return Vec3::ZERO;
+				};
+				if points.len() == 1 {
+					return last;
+				}
+				let segments = points.len() - 1;
+				let (index, t) = segment(t, segments);
+				let point = |i: isize| points[i.clamp(0, segments as isize) as usize];
+				let index = index as isize;
+				let (p0, p1, p2, p3) = (point(index - 1), point(index), point(index + 1), point(index + 2));
+				0.5 * (2. * p1
+					+ (p2 - p0) * t
+					+ (2. * p0 - 5. * p1 + 4. * p2 - p3) * t * t
+					+ (3. * p1 - p0 - 3. * p2 + p3) * t * t * t)
+			},
+			Self::Bezier(points) => {
+				if points.len() < 4 {
+					return points.first().copied().unwrap_or_default();
+				}
+				let (index, t) = segment(t, (points.len() - 1) / 3);
+				let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|offset| points[index * 3 + offset]);
+				let u = 1. - t;
+				u * u * u * p0 + 3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t * p3
+			},
+		}
+	}
+}
+
+/* splits a path parameter into a segment and the parameter within it */
+fn segment(t: f32, segments: usize) -> (usize, f32) {
+	let scaled = t.clamp(0., 1.) * segments as f32;
+	let index = (scaled.floor() as usize).min(segments - 1);
+	(index, scaled - index as f32)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum LookAt {
+	Point(Vec3),
+	/* the first entity with this name, characters are named in the world */
+	Named(String),
+	/* along the path */
+	Forward,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct FovKey {
+	/* fraction of the shot */
+	pub time: f32,
+	pub degrees: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CameraShot {
+	pub duration: f32,
+	pub path: CameraPath,
+	pub look_at: LookAt,
+	#[serde(default)]
+	pub easing: Easing,
+	/* the camera keeps its field of view when empty */
+	#[serde(default)]
+	pub fov: Vec<FovKey>,
+}
+
+impl CameraShot {
+	fn fov(&self, t: f32) -> Option<f32> {
+		let first = self.fov.first()?;
+		let next = self.fov.iter().position(|key| key.time > t);
+		let degrees = match next {
+			Some(0) => first.degrees,
+			Some(index) => {
+				let (from, to) = (self.fov[index - 1], self.fov[index]);
+				from.degrees + (to.degrees - from.degrees) * (t - from.time) / (to.time - from.time)
+			},
+			None => self.fov.last()?.degrees,
+		};
+		Some(degrees.to_radians())
+	}
+}
+
+/* a cutscene, loaded from `.shots.ron` files */
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug)]
+pub struct CameraSequence {
+	pub shots: Vec<CameraShot>,
+	/* seconds spent easing back into the gameplay camera */
+	#[serde(default = "CameraSequence::default_blend_out")]
+	pub blend_out: f32,
+}
+
+impl CameraSequence {
+	fn default_blend_out() -> f32 {
+		1.
+	}
+}
+
+#[derive(Default)]
+pub struct CameraSequenceLoader;
+
+impl AssetLoader for CameraSequenceLoader {
+	type Asset = CameraSequence;
+	type Settings = ();
+	type Error = String;
+
+	fn load<'a>(
+		&'a self,
+		reader: &'a mut Reader<'_>,
+		_settings: &'a (),
+		_load_context: &'a mut LoadContext<'_>,
+	) -> impl ConditionalSendFuture<Output = Result<CameraSequence, String>> {
+		async move {
+			let mut contents = String::new();
+			reader.read_to_string(&mut contents).await.map_err(|error| error.to_string())?;
+			ron::from_str(&contents).map_err(|error| error.to_string())
+		}
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["shots.ron"]
+	}
+}
+
+/* switches the camera to the sequence, returning to the current mode afterwards */
+#[derive(Event, Clone, Debug)]
+pub struct PlayCinematic(pub Handle<CameraSequence>);
+
+/* the running sequence, present while the camera is in the cinematic mode */
+#[derive(Resource, Clone, Debug)]
+pub struct Cinematic {
+	pub sequence: Handle<CameraSequence>,
+	shot: usize,
+	elapsed: f32,
+	/* gameplay camera to return to */
+	previous: CameraMode,
+	resume: Transform,
+	resume_fov: Option<f32>,
+	/* where the blend back starts from, set once the last shot ends */
+	blend_from: Option<(Transform, Option<f32>)>,
+}
+
+type CinematicCameraQuery<'w, 's> = Query<'w, 's, (&'static mut Transform, Option<&'static mut Projection>), (With<Camera>, Without<SplitCamera>)>;
+
+fn perspective_fov(projection: Option<&Projection>) -> Option<f32> {
+	match projection {
+		Some(Projection::Perspective(perspective)) => Some(perspective.fov),
+		_ => None,
+	}
+}
+
+fn set_fov(projection: Option<Mut<Projection>>, fov: Option<f32>) {
+	if let (Some(mut projection), Some(fov)) = (projection, fov) {
+		if let Projection::Perspective(perspective) = projection.as_mut() {
+			perspective.fov = fov;
+		}
+	}
+}
+
+pub(in crate::control) fn play_cinematic(
+	mut commands: Commands,
+	mut play_events: EventReader<PlayCinematic>,
+	camera_state: Res<State<CameraMode>>,
+	mut next_camera_state: ResMut<NextState<CameraMode>>,
+	cinematic: Option<Res<Cinematic>>,
+	camera_query: Query<(&Transform, Option<&Projection>), (With<Camera>, Without<SplitCamera>)>,
+) {
+	let Some(PlayCinematic(sequence)) = play_events.read().last() else {
+		return;
+	};
+	let Some((&camera_transform, projection)) = camera_query.iter().next() else {
+		return;
+	};
+	/* a sequence started mid cutscene returns to the original gameplay camera */
+	let (previous, resume, resume_fov) = match cinematic {
+		Some(cinematic) => (cinematic.previous.clone(), cinematic.resume, cinematic.resume_fov),
+		None => (camera_state.get().clone(), camera_transform, perspective_fov(projection)),
+	};
+	commands.insert_resource(Cinematic {
+		sequence: sequence.clone(),
+		shot: 0,
+		elapsed: 0.,
+		previous,
+		resume,
+		resume_fov,
+		blend_from: None,
+	});
+	next_camera_state.set(CameraMode::Cinematic);
+}
+
+pub(in crate::control) fn camera_cinematic(
+	mut commands: Commands,
+	cinematic: Option<ResMut<Cinematic>>,
+	sequences: Res<Assets<CameraSequence>>,
+	asset_server: Res<AssetServer>,
+	mut camera_query: CinematicCameraQuery,
+	name_query: Query<(&Name, &GlobalTransform)>,
+	mut next_camera_state: ResMut<NextState<CameraMode>>,
+	fallback: Res<CameraFallback>,
+	time: Res<Time>,
+) {
+	/* entered without a sequence to play */
+	let Some(mut cinematic) = cinematic else {
+		next_camera_state.set(fallback.0.clone());
+		return;
+	};
+	let Some(sequence) = sequences.get(&cinematic.sequence) else {
+		/* still loading, or never will */
+		if let Some(LoadState::Failed(_)) = asset_server.get_load_state(&cinematic.sequence) {
+			next_camera_state.set(cinematic.previous.clone());
+			commands.remove_resource::<Cinematic>();
+		}
+		return;
+	};
+
+	cinematic.elapsed += time.delta_seconds();
+	while let Some(shot) = sequence.shots.get(cinematic.shot).filter(|shot| cinematic.elapsed >= shot.duration) {
+		cinematic.elapsed -= shot.duration;
+		cinematic.shot += 1;
+	}
+
+	if let Some(shot) = sequence.shots.get(cinematic.shot) {
+		let t = shot.easing.apply(cinematic.elapsed / shot.duration.max(f32::EPSILON));
+		let position = shot.path.position(t);
+		let target = match &shot.look_at {
+			LookAt::Point(point) => *point,
+			LookAt::Named(name) => name_query.iter()
+				.find(|(other, _)| other.as_str() == name)
+				.map_or(position + Vec3::NEG_Z, |(_, transform)| transform.translation()),
+			LookAt::Forward => position + (shot.path.position((t + 0.01).min(1.)) - shot.path.position((t - 0.01).max(0.))),
+		};
+		for (mut camera_transform, projection) in camera_query.iter_mut() {
+			camera_transform.translation = position;
+			if target != position {
+				camera_transform.look_at(target, Vec3::Y);
+			}
+			set_fov(projection, shot.fov(t));
+		}
+		return;
+	}
+
+	/* every shot has played, ease back into the gameplay camera */
+	let Some((mut camera_transform, projection)) = camera_query.iter_mut().next() else {
+		return;
+	};
+	let (from, from_fov) = *cinematic.blend_from.get_or_insert((*camera_transform, perspective_fov(projection.as_deref())));
+	let t = Easing::EaseInOut.apply(cinematic.elapsed / sequence.blend_out.max(f32::EPSILON));
+	camera_transform.translation = from.translation.lerp(cinematic.resume.translation, t);
+	camera_transform.rotation = from.rotation.slerp(cinematic.resume.rotation, t);
+	let fov = from_fov.zip(cinematic.resume_fov).map(|(from, to)| from + (to - from) * t);
+	set_fov(projection, fov);
+	if t >= 1. {
+		next_camera_state.set(cinematic.previous.clone());
+		commands.remove_resource::<Cinematic>();
+	}
+}
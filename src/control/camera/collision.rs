@@ -0,0 +1,179 @@
+use super::{
+	CameraMode,
+	super::{
+		Controlling,
+		PlayerCamera,
+		SplitCamera
+	},
+};
+
+use bevy::prelude::*;
+use avian3d::prelude::*;
+
+use world::SpatialTypes;
+
+/* keeps following cameras out of level geometry between them and their target */
+#[derive(Component, Clone, Debug)]
+pub struct CameraCollision {
+	pub radius: f32,
+	/* distance kept from the hit surface */
+	pub margin: f32,
+	pub min_distance: f32,
+	/* meters per second the camera moves back out once unobstructed */
+	pub ease_out_speed: f32,
+	pub fade_occluders: bool,
+	pub occluder_alpha: f32,
+	/* where the camera modes placed the camera, before collision pulled it in */
+	desired: Option<Vec3>,
+	distance: Option<f32>,
+}
+
+impl Default for CameraCollision {
+	fn default() -> Self {
+		Self {
+			radius: 0.2,
+			margin: 0.05,
+			min_distance: 0.3,
+			ease_out_speed: 2.,
+			fade_occluders: true,
+			occluder_alpha: 0.3,
+			desired: None,
+			distance: None,
+		}
+	}
+}
+
+/* material an occluder had before it was faded */
+#[derive(Component)]
+pub struct FadedOccluder(Handle<StandardMaterial>);
+
+/* hands the unobstructed position back to the camera modes, which orbit relative to it */
+pub(in crate::control) fn restore_camera_collision(
+	mut camera_query: Query<(&mut Transform, &CameraCollision), With<Camera>>,
+) {
+	for (mut camera_transform, collision) in camera_query.iter_mut() {
+		if let Some(desired) = collision.desired {
+			camera_transform.translation = desired;
+		}
+	}
+}
+
+pub(in crate::control) fn camera_collision(
+	mut camera_query: Query<(&mut Transform, &mut CameraCollision, Option<&PlayerCamera>, Has<SplitCamera>), With<Camera>>,
+	target_query: Query<&GlobalTransform>,
+	controlling_query: Query<(&GlobalTransform, &Controlling)>,
+	camera_state: Res<State<CameraMode>>,
+	spatial_query: SpatialQuery,
+	time: Res<Time>,
+) {
+	for (mut camera_transform, mut collision, player_camera, split) in camera_query.iter_mut() {
+		let focus = if split {
+			player_camera.and_then(|player_camera| controlling_query.iter()
+				.find(|(_, controlling)| controlling.0 == player_camera.0)
+				.map(|(transform, _)| transform.translation() + Vec3::Y))
+		} else {
+			match *camera_state.get() {
+				CameraMode::Following(entity) | CameraMode::FreeFollowing(entity) | CameraMode::LockOn(entity, _) => target_query.get(entity).ok().map(GlobalTransform::translation),
+				_ => None,
+			}
+		};
+		let Some(focus) = focus else {
+			collision.desired = None;
+			collision.distance = None;
+			continue;
+		};
+
+		let desired = camera_transform.translation;
+		collision.desired = Some(desired);
+		let offset = desired - focus;
+		let (Ok(direction), desired_distance) = (Dir3::new(offset), offset.length()) else {
+			continue;
+		};
+		let allowed = spatial_query.cast_shape(
+			&Collider::sphere(collision.radius),
+			focus,
+			Quat::IDENTITY,
+			direction,
+			desired_distance,
+			true,
+			SpatialQueryFilter::from_mask(SpatialTypes::World),
+		).map_or(desired_distance, |hit| (hit.time_of_impact - collision.margin).max(collision.min_distance));
+
+		/* snap in when obstructed, ease back out */
+		let current = collision.distance.unwrap_or(desired_distance);
+		let distance = if allowed < current {
+			allowed
+		} else {
+			(current + collision.ease_out_speed * time.delta_seconds()).min(allowed)
+		};
+		collision.distance = Some(distance);
+		camera_transform.translation = focus + direction * distance;
+	}
+}
+
+pub(in crate::control) fn fade_occluders(
+	mut commands: Commands,
+	camera_query: Query<(&GlobalTransform, &CameraCollision), With<Camera>>,
+	camera_state: Res<State<CameraMode>>,
+	target_query: Query<&GlobalTransform>,
+	mut material_query: Query<&mut Handle<StandardMaterial>>,
+	children_query: Query<&Children>,
+	faded_query: Query<(Entity, &FadedOccluder)>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	spatial_query: SpatialQuery,
+) {
+	let mut occluders: Vec<(Entity, f32)> = Vec::new();
+	for (camera_transform, collision) in camera_query.iter() {
+		if !collision.fade_occluders {
+			continue;
+		}
+		let target = match *camera_state.get() {
+			CameraMode::Following(entity) | CameraMode::FreeFollowing(entity) | CameraMode::LockOn(entity, _) => entity,
+			_ => continue,
+		};
+		let Ok(target_transform) = target_query.get(target) else {
+			continue;
+		};
+		let origin = camera_transform.translation();
+		let offset = target_transform.translation() - origin;
+		let Ok(direction) = Dir3::new(offset) else {
+			continue;
+		};
+		for hit in spatial_query.ray_hits(origin, direction, offset.length(), 8, true, SpatialQueryFilter::from_mask(SpatialTypes::World)) {
+			/* colliders are often parents of the meshes they stand in for */
+			let meshes = std::iter::once(hit.entity).chain(children_query.get(hit.entity).into_iter().flatten().copied());
+			for entity in meshes {
+				if !occluders.iter().any(|&(other, _)| other == entity) {
+					occluders.push((entity, collision.occluder_alpha));
+				}
+			}
+		}
+	}
+
+	for &(entity, alpha) in occluders.iter() {
+		if faded_query.contains(entity) {
+			continue;
+		}
+		let Ok(mut handle) = material_query.get_mut(entity) else {
+			continue;
+		};
+		let Some(mut faded) = materials.get(handle.id()).cloned() else {
+			continue;
+		};
+		faded.base_color.set_alpha(alpha);
+		faded.alpha_mode = AlphaMode::Blend;
+		let original = std::mem::replace(handle.as_mut(), materials.add(faded));
+		commands.entity(entity).insert(FadedOccluder(original));
+	}
+
+	/* restore what no longer occludes */
+	for (entity, faded) in faded_query.iter() {
+		if occluders.iter().any(|&(other, _)| other == entity) {
+			continue;
+		}
+		if let Ok(mut handle) = material_query.get_mut(entity) {
+			*handle = faded.0.clone();
+		}
+		commands.entity(entity).remove::<FadedOccluder>();
+	}
+}
@@ -0,0 +1,183 @@
+use super::CameraMode;
+
+use std::f32::consts::{
+	FRAC_PI_2,
+	PI,
+	TAU
+};
+
+use bevy::{
+	prelude::*,
+	ecs::system::SystemParam,
+	render::view::RenderLayers,
+};
+use avian3d::prelude::*;
+
+use character::CharacterController;
+
+/* meshes hidden from the first person camera move here, every other camera renders it as well */
+pub const FIRST_PERSON_HIDDEN_LAYER: usize = 1;
+
+#[derive(Resource, Clone, Debug)]
+pub struct FirstPersonSettings {
+	/* bone the camera is attached to, as named in the glTF */
+	pub head_bone: String,
+	/* nodes whose meshes the local view hides, along with everything beneath them */
+	pub hidden: Vec<String>,
+	/* from the head bone, in the character's facing */
+	pub eye_offset: Vec3,
+	/* above the character's origin, used until the head bone has loaded */
+	pub eye_height: f32,
+	pub min_pitch: f32,
+	pub max_pitch: f32,
+	/* sideways and vertical sway at full speed */
+	pub bob_amplitude: Vec2,
+	/* steps per meter travelled, the sway swings once every two steps */
+	pub bob_frequency: f32,
+	/* speed the bob reaches its full amplitude at */
+	pub bob_speed: f32,
+}
+
+impl Default for FirstPersonSettings {
+	fn default() -> Self {
+		Self {
+			head_bone: String::from("head"),
+			hidden: vec![String::from("head")],
+			eye_offset: Vec3::new(0., 0.08, -0.1),
+			eye_height: 1.6,
+			min_pitch: -FRAC_PI_2 + 0.1,
+			max_pitch: FRAC_PI_2 - 0.1,
+			bob_amplitude: Vec2::new(0.03, 0.04),
+			bob_frequency: 0.7,
+			bob_speed: 5.,
+		}
+	}
+}
+
+/* the character the camera is behind the eyes of, and what was hidden for it */
+#[derive(Resource, Default, Debug)]
+pub struct FirstPerson {
+	character: Option<Entity>,
+	head: Option<Entity>,
+	hidden: Vec<Entity>,
+	yaw: f32,
+	pitch: f32,
+	bob_phase: f32,
+	bob_weight: f32,
+	/* picks up the camera's orientation when a character is entered */
+	synced: bool,
+}
+
+impl FirstPerson {
+	pub fn head(&self) -> Option<Entity> {
+		self.head
+	}
+	/* starts looking the way the camera already faces */
+	pub(super) fn sync(&mut self, camera_transform: &Transform, settings: &FirstPersonSettings) {
+		let (yaw, pitch, _) = camera_transform.rotation.to_euler(EulerRot::YXZ);
+		self.yaw = yaw;
+		self.pitch = pitch.clamp(settings.min_pitch, settings.max_pitch);
+		self.synced = true;
+	}
+	pub(super) fn is_synced(&self) -> bool {
+		self.synced
+	}
+	pub(super) fn look(&mut self, delta: Vec2, sensitivity: f32, settings: &FirstPersonSettings) {
+		self.yaw -= delta.x * sensitivity;
+		self.pitch = (self.pitch - delta.y * sensitivity).clamp(settings.min_pitch, settings.max_pitch);
+	}
+	/* eye transform with head bob layered on */
+	pub(super) fn eye(&mut self, body: Body, settings: &FirstPersonSettings, delta_seconds: f32) -> Transform {
+		let facing = Quat::from_rotation_y(self.yaw);
+		let head = body.head
+			.map(GlobalTransform::translation)
+			.unwrap_or(body.transform.translation + Vec3::Y * settings.eye_height);
+
+		let speed = if body.grounded {
+			body.velocity.with_y(0.).length()
+		} else {
+			0.
+		};
+		let weight = (speed / settings.bob_speed).min(1.);
+		self.bob_weight += (weight - self.bob_weight) * (1. - (-10. * delta_seconds).exp());
+		self.bob_phase = (self.bob_phase + speed * settings.bob_frequency * PI * delta_seconds).rem_euclid(TAU);
+		let bob = Vec3::new(
+			self.bob_phase.sin() * settings.bob_amplitude.x,
+			self.bob_phase.sin().abs() * settings.bob_amplitude.y,
+			0.
+		) * self.bob_weight;
+
+		Transform::from_translation(head + facing * (settings.eye_offset + bob))
+			.with_rotation(Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.))
+	}
+}
+
+/* what the first person view needs to know about the character this frame */
+#[derive(Clone, Copy)]
+pub(super) struct Body<'a> {
+	pub transform: &'a Transform,
+	pub head: Option<&'a GlobalTransform>,
+	pub velocity: Vec3,
+	pub grounded: bool,
+}
+
+/* the first person state and the character parts the perspective camera reads */
+#[derive(SystemParam)]
+pub(in crate::control) struct FirstPersonView<'w, 's> {
+	pub(super) settings: Res<'w, FirstPersonSettings>,
+	pub(super) first_person: ResMut<'w, FirstPerson>,
+	pub(super) body_query: Query<'w, 's, (Option<&'static LinearVelocity>, Option<&'static CharacterController>)>,
+	pub(super) bone_query: Query<'w, 's, &'static GlobalTransform>,
+}
+
+/* finds the head bone and hides the head once the character's scene has spawned */
+pub(in crate::control) fn update_first_person_body(
+	mut commands: Commands,
+	camera_state: Res<State<CameraMode>>,
+	settings: Res<FirstPersonSettings>,
+	mut first_person: ResMut<FirstPerson>,
+	children_query: Query<&Children>,
+	name_query: Query<&Name>,
+	mesh_query: Query<(), With<Handle<Mesh>>>,
+) {
+	let character = match *camera_state.get() {
+		CameraMode::Perspective(entity) => Some(entity),
+		_ => None,
+	};
+	if character != first_person.character {
+		for entity in first_person.hidden.drain(..) {
+			if let Some(mut entity_commands) = commands.get_entity(entity) {
+				entity_commands.remove::<RenderLayers>();
+			}
+		}
+		first_person.head = None;
+		first_person.character = character;
+		first_person.synced = false;
+	}
+	let Some(character) = character else {
+		return;
+	};
+	if first_person.head.is_some() {
+		return;
+	}
+
+	for entity in children_query.iter_descendants(character) {
+		let Ok(name) = name_query.get(entity) else {
+			continue;
+		};
+		if name.as_str() == settings.head_bone {
+			first_person.head = Some(entity);
+		}
+		if settings.hidden.iter().any(|hidden| hidden == name.as_str()) {
+			let meshes = std::iter::once(entity)
+				.chain(children_query.iter_descendants(entity))
+				.filter(|&mesh| mesh_query.contains(mesh));
+			for mesh in meshes {
+				if !first_person.hidden.contains(&mesh) {
+					commands.entity(mesh).insert(RenderLayers::layer(FIRST_PERSON_HIDDEN_LAYER));
+					first_person.hidden.push(mesh);
+				}
+			}
+		}
+	}
+}
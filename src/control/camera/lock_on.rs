@@ -0,0 +1,212 @@
+use super::{
+	CameraMode,
+	super::{
+		Controlling,
+		Player,
+		PlayerCamera,
+		SplitCamera,
+		Hotkey,
+		InputContextStack,
+		bindings::Bindings,
+		possession::hotkey_player,
+	},
+};
+
+use bevy::prelude::*;
+use avian3d::prelude::*;
+use bevy_persistent::prelude::*;
+use bevy_egui::EguiContexts;
+
+use character::CharacterController;
+use world::SpatialTypes;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LockOnAction {
+	Toggle,
+	Cycle,
+}
+
+/* sent by the lock on hotkeys, acts on the player's camera */
+#[derive(Event, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LockOn {
+	pub player: Player,
+	pub action: LockOnAction,
+}
+
+/* the lock of a split camera, the primary camera keeps its lock in CameraMode::LockOn */
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LockOnTarget {
+	pub character: Entity,
+	pub target: Entity,
+}
+
+impl LockOnTarget {
+	/* whichever lock drives the camera */
+	pub fn of_camera(camera_state: &CameraMode, split: bool, lock_on_target: Option<&LockOnTarget>) -> Option<Self> {
+		match (camera_state, split) {
+			(_, true) => lock_on_target.copied(),
+			(&CameraMode::LockOn(character, target), false) => Some(Self {
+				character,
+				target,
+			}),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct LockOnSettings {
+	pub range: f32,
+	/* half angle of the cone in front of the camera targets are picked from */
+	pub view_angle: f32,
+	/* height above the feet both characters are framed at */
+	pub height: f32,
+	/* camera distance per meter between the characters, keeps both in frame */
+	pub framing: f32,
+	/* how quickly the camera swings around behind the character */
+	pub turn_speed: f32,
+}
+
+impl Default for LockOnSettings {
+	fn default() -> Self {
+		Self {
+			range: 15.,
+			view_angle: 0.6,
+			height: 1.,
+			framing: 1.2,
+			turn_speed: 6.,
+		}
+	}
+}
+
+/* characters in range and in view of the camera, nearest first */
+fn lock_on_candidates(
+	spatial_query: &SpatialQuery,
+	settings: &LockOnSettings,
+	camera_transform: &Transform,
+	character: Entity,
+	position: Vec3,
+	character_query: &Query<(Entity, &GlobalTransform), With<CharacterController>>,
+) -> Vec<Entity> {
+	let mut candidates: Vec<(Entity, f32)> = character_query.iter()
+		.filter(|&(candidate, _)| candidate != character)
+		.filter_map(|(candidate, candidate_transform)| {
+			let distance = candidate_transform.translation().distance(position);
+			if distance > settings.range {
+				return None;
+			}
+			let focus = candidate_transform.translation() + Vec3::Y * settings.height;
+			let offset = focus - camera_transform.translation;
+			if offset.angle_between(camera_transform.forward().as_vec3()) > settings.view_angle {
+				return None;
+			}
+			let direction = Dir3::new(offset).ok()?;
+			let blocked = spatial_query.cast_ray(
+				camera_transform.translation,
+				direction,
+				offset.length(),
+				true,
+				SpatialQueryFilter::from_mask(SpatialTypes::World),
+			).is_some();
+			(!blocked).then_some((candidate, distance))
+		})
+		.collect();
+	candidates.sort_by(|(_, x), (_, y)| x.total_cmp(y));
+	candidates.into_iter().map(|(entity, _)| entity).collect()
+}
+
+/* kept apart from the control plugin so it can run headless */
+pub struct LockOnPlugin;
+
+impl Plugin for LockOnPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<LockOn>()
+			.init_resource::<LockOnSettings>()
+			.add_systems(Update, lock_on
+				.after(lock_on_hotkeys)
+				.before(super::camera_control)
+			);
+	}
+}
+
+/* added by the control plugin, the headless tests send LockOn directly */
+pub(in crate::control) fn lock_on_hotkeys(
+	key_input: Res<ButtonInput<KeyCode>>,
+	bindings: Res<Persistent<Bindings>>,
+	context_stack: Res<InputContextStack>,
+	mut contexts: EguiContexts,
+	mut lock_on_events: EventWriter<LockOn>,
+) {
+	let Some(player) = hotkey_player(bindings.get(), &context_stack, &mut contexts) else {
+		return;
+	};
+	for key in key_input.get_just_pressed() {
+		let action = match bindings.get().hotkeys.get(key) {
+			Some(Hotkey::LockOn) => LockOnAction::Toggle,
+			Some(Hotkey::CycleLockOn) => LockOnAction::Cycle,
+			_ => continue,
+		};
+		lock_on_events.send(LockOn {
+			player,
+			action,
+		});
+	}
+}
+
+fn lock_on(
+	mut commands: Commands,
+	mut lock_on_events: EventReader<LockOn>,
+	camera_state: Res<State<CameraMode>>,
+	mut next_camera_state: ResMut<NextState<CameraMode>>,
+	settings: Res<LockOnSettings>,
+	camera_query: Query<(Entity, &Transform, &PlayerCamera, Has<SplitCamera>, Option<&LockOnTarget>), With<Camera>>,
+	controlling_query: Query<(Entity, &Controlling)>,
+	character_query: Query<(Entity, &GlobalTransform), With<CharacterController>>,
+	spatial_query: SpatialQuery,
+) {
+	let events: Vec<LockOn> = lock_on_events.read().copied().collect();
+	for (camera, camera_transform, &PlayerCamera(player), split, lock_on_target) in camera_query.iter() {
+		let action = events.iter().rev().find(|event| event.player == player).map(|event| event.action);
+		let controlled = controlling_query.iter().find(|(_, controlling)| controlling.0 == player).map(|(entity, _)| entity);
+		let candidates = |character: Entity| character_query.get(character)
+			.map(|(_, transform)| lock_on_candidates(&spatial_query, &settings, camera_transform, character, transform.translation(), &character_query))
+			.unwrap_or_default();
+
+		/* some(none) releases the lock */
+		let next = match (LockOnTarget::of_camera(camera_state.get(), split, lock_on_target), action) {
+			(Some(lock), Some(LockOnAction::Toggle)) => Some((lock.character, None)),
+			(Some(lock), Some(LockOnAction::Cycle)) => {
+				let candidates = candidates(lock.character);
+				let next = candidates.iter().position(|&candidate| candidate == lock.target)
+					.map_or(0, |index| (index + 1) % candidates.len());
+				candidates.get(next).map(|&next| (lock.character, Some(next)))
+			},
+			/* vanished targets are replaced by the nearest one, targets out of range or a lost character release the lock */
+			(Some(lock), None) => match (character_query.get(lock.character), character_query.get(lock.target)) {
+				_ if controlled != Some(lock.character) => Some((lock.character, None)),
+				(Ok((_, character_transform)), Ok((_, target_transform))) => (character_transform.translation().distance(target_transform.translation()) > settings.range)
+					.then_some((lock.character, None)),
+				_ => Some((lock.character, candidates(lock.character).first().copied())),
+			},
+			(None, Some(LockOnAction::Toggle)) => controlled
+				.and_then(|character| candidates(character).first().map(|&target| (character, Some(target)))),
+			_ => None,
+		};
+		let Some((character, target)) = next else {
+			continue;
+		};
+		match (split, target) {
+			(true, Some(target)) => {
+				commands.entity(camera).insert(LockOnTarget {
+					character,
+					target,
+				});
+			},
+			(true, None) => {
+				commands.entity(camera).remove::<LockOnTarget>();
+			},
+			(false, Some(target)) => next_camera_state.set(CameraMode::LockOn(character, target)),
+			(false, None) => next_camera_state.set(CameraMode::Following(character)),
+		}
+	}
+}
@@ -0,0 +1,341 @@
+use std::f32::consts::{
+	FRAC_PI_2,
+	PI,
+	TAU
+};
+
+use bevy::{
+	prelude::*,
+	ecs::system::SystemParam,
+	input::mouse::{
+		MouseButton,
+		MouseMotion,
+		MouseScrollUnit,
+		MouseWheel,
+	}
+};
+use bevy_egui::EguiContexts;
+
+use character::CharacterController;
+
+use super::SplitCamera;
+
+mod collision;
+mod rig;
+mod target;
+mod cinematic;
+mod lock_on;
+mod shake;
+mod first_person;
+
+pub use collision::*;
+pub use rig::*;
+pub use target::*;
+pub use cinematic::*;
+pub use lock_on::*;
+pub use shake::*;
+pub use first_person::*;
+
+/* pixel scrolling devices report roughly this much per line */
+const SCROLL_PIXELS_PER_LINE: f32 = 20.;
+
+/* look gestures from touch controls, consumed by the camera each frame */
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct TouchCameraInput {
+	pub orbit: Vec2,
+	pub zoom: f32,
+}
+
+/* camera modes drive the first player's camera, split cameras follow their own player */
+type PrimaryCameraQuery<'w, 's> = Query<'w, 's, (&'static mut Transform, &'static mut CameraRig), (With<Camera>, Without<SplitCamera>)>;
+
+/* one global mode for the primary camera, split cameras don't take part, see SplitCamera */
+#[derive(States, Default, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum CameraMode {
+	#[default]
+	Tracked,
+	FreePerspective,
+	FreeFollowing(Entity),
+	Perspective(Entity),
+	Following(Entity),
+	/* follows the character while keeping the second entity in frame */
+	LockOn(Entity, Entity),
+	/* driven by the running cinematic sequence */
+	Cinematic,
+}
+
+/* everything the camera modes read from input this frame */
+#[derive(Clone, Copy, Debug, Default)]
+struct CameraInput {
+	/* summed pointer motion in pixels */
+	look: Vec2,
+	/* in lines, positive towards the target */
+	scroll: f32,
+	touch: TouchCameraInput,
+}
+
+impl CameraInput {
+	/* mouse motion, falling back to touch orbiting */
+	fn look(&self) -> Option<Vec2> {
+		[self.look, self.touch.orbit].into_iter().find(|&delta| delta != Vec2::ZERO)
+	}
+}
+
+/* mouse and touch sources of the camera input */
+#[derive(SystemParam)]
+pub(in crate::control) struct CameraInputReader<'w, 's> {
+	motion_reader: EventReader<'w, 's, MouseMotion>,
+	wheel_reader: EventReader<'w, 's, MouseWheel>,
+	mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+	touch_input: ResMut<'w, TouchCameraInput>,
+	context: EguiContexts<'w, 's>,
+}
+
+impl CameraInputReader<'_, '_> {
+	/* drains this frame's input, none while the pointer is over a menu */
+	fn read(&mut self) -> Option<CameraInput> {
+		let input = CameraInput {
+			look: self.motion_reader.read().map(|motion| motion.delta).sum(),
+			scroll: self.wheel_reader.read().map(|wheel| match wheel.unit {
+				MouseScrollUnit::Line => wheel.y,
+				MouseScrollUnit::Pixel => wheel.y / SCROLL_PIXELS_PER_LINE,
+			}).sum(),
+			touch: std::mem::take(self.touch_input.as_mut()),
+		};
+		(!self.context.ctx_mut().wants_pointer_input()).then_some(input)
+	}
+}
+
+pub(super) fn camera_control(
+	mut camera_query: PrimaryCameraQuery,
+	mut transform_query: Query<&mut Transform, Without<Camera>>,
+	mut input_reader: CameraInputReader,
+	time: Res<Time>,
+	camera_state: Res<State<CameraMode>>,
+	lock_on_settings: Res<LockOnSettings>,
+	mut first_person_view: FirstPersonView,
+) {
+	let Some(input) = input_reader.read() else {
+		return;
+	};
+	let mode = camera_state.get().clone();
+	/* only the following modes own the rig, the others move the camera themselves */
+	if !matches!(mode, CameraMode::Following(_) | CameraMode::LockOn(..)) {
+		for (_, mut rig) in camera_query.iter_mut() {
+			rig.release();
+		}
+	}
+	match mode {
+		CameraMode::FreePerspective => camera_free_perspective(
+			camera_query,
+			input,
+		),
+		/* lost targets are handled by validate_camera_target, the camera holds still until the fallback applies */
+		CameraMode::FreeFollowing(entity) => if let Ok(target_transform) = transform_query.get_mut(entity) {
+			camera_free_following(
+				camera_query,
+				target_transform,
+				input,
+				&input_reader.mouse_buttons,
+			)
+		},
+		CameraMode::Perspective(entity) => if let Ok(target_transform) = transform_query.get(entity) {
+			camera_perspective(
+				camera_query,
+				entity,
+				target_transform,
+				&mut first_person_view,
+				input,
+				time,
+			)
+		},
+		CameraMode::Following(entity) => if let Ok(target_transform) = transform_query.get(entity) {
+			camera_following(
+				camera_query,
+				target_transform,
+				input,
+				time,
+			)
+		},
+		CameraMode::LockOn(character, target) => if let (Ok(character_transform), Ok(target_transform)) = (transform_query.get(character), transform_query.get(target)) {
+			camera_lock_on(
+				camera_query,
+				character_transform,
+				target_transform,
+				&lock_on_settings,
+				input,
+				time,
+			)
+		},
+		_ => ()
+	}
+}
+
+fn look_around(camera_transform: &mut Transform, delta: Vec2, sensitivity: f32) {
+	let (yaw, pitch, _) = camera_transform.rotation.to_euler(EulerRot::YXZ);
+	let yaw = yaw - delta.x * sensitivity;
+	let pitch = (pitch - delta.y * sensitivity).clamp(-FRAC_PI_2 + 0.01, FRAC_PI_2 - 0.01);
+	camera_transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.);
+}
+
+fn orbit(camera_transform: &mut Transform, target: Vec3, delta: Vec2, sensitivity: f32) {
+	let distance = (camera_transform.translation - target).length();
+	camera_transform.look_at(target, Vec3::Y);
+	look_around(camera_transform, delta, sensitivity);
+	camera_transform.translation = target + camera_transform.back() * distance;
+}
+
+/* positive zoom moves towards the target without passing it */
+fn zoom(camera_transform: &mut Transform, target: Vec3, zoom: f32) {
+	if zoom == 0. {
+		return;
+	}
+	let offset = camera_transform.translation - target;
+	let distance = (offset.length() - zoom).max(0.5);
+	camera_transform.translation = target + offset.normalize_or_zero() * distance;
+}
+
+fn camera_free_perspective(
+	mut camera_query: PrimaryCameraQuery,
+	input: CameraInput,
+) {
+	if let Some(delta) = input.look() {
+		for (mut camera_transform, rig) in camera_query.iter_mut() {
+			look_around(&mut camera_transform, delta, rig.sensitivity);
+		}
+	}
+}
+
+fn camera_free_following(
+	mut camera_query: PrimaryCameraQuery,
+	mut target_transform: Mut<Transform>,
+	input: CameraInput,
+	mouse_buttons: &ButtonInput<MouseButton>,
+) {
+	/* touch orbits and pinches, it has no panning gesture */
+	for (mut camera_transform, rig) in camera_query.iter_mut() {
+		if input.touch.orbit != Vec2::ZERO {
+			orbit(&mut camera_transform, target_transform.translation, input.touch.orbit, rig.sensitivity);
+		}
+		zoom(&mut camera_transform, target_transform.translation, input.touch.zoom + input.scroll * rig.zoom_sensitivity);
+	}
+	if input.look == Vec2::ZERO {
+		return;
+	}
+	let delta = input.look;
+	for button in mouse_buttons.get_pressed() {
+		match button {
+			/* Rotate */
+			MouseButton::Left => {
+				for (mut camera_transform, rig) in camera_query.iter_mut() {
+					orbit(&mut camera_transform, target_transform.translation, delta, rig.sensitivity);
+				}
+			},
+			/* Pan, scaled by distance so the target tracks the pointer */
+			MouseButton::Middle => {
+				for (mut camera_transform, rig) in camera_query.iter_mut() {
+					let scale = (camera_transform.translation - target_transform.translation).length() * rig.sensitivity;
+					let right = camera_transform.right() * delta.x * scale;
+					let down = camera_transform.down() * delta.y * scale;
+					camera_transform.translation += right;
+					camera_transform.translation += down;
+					target_transform.translation += right;
+					target_transform.translation += down;
+				}
+			},
+			/* Zoom + Translate Y */
+			MouseButton::Right => {
+				for (mut camera_transform, rig) in camera_query.iter_mut() {
+					let scale = (camera_transform.translation - target_transform.translation).length() * rig.sensitivity;
+					let neg_y = Vec3::NEG_Y * delta.y * scale;
+					camera_transform.translation += neg_y;
+					target_transform.translation += neg_y;
+					zoom(&mut camera_transform, target_transform.translation, delta.x * scale);
+				}
+			},
+			_ => ()
+		}
+	}
+}
+
+/* behind the character's eyes, the head stays hidden from this view */
+fn camera_perspective(
+	mut camera_query: PrimaryCameraQuery,
+	entity: Entity,
+	target_transform: &Transform,
+	view: &mut FirstPersonView,
+	input: CameraInput,
+	time: Res<Time>,
+) {
+	let FirstPersonView { settings, first_person, body_query, bone_query } = view;
+	let (velocity, controller) = body_query.get(entity).unwrap_or((None, None));
+	let body = Body {
+		transform: target_transform,
+		head: first_person.head().and_then(|head| bone_query.get(head).ok()),
+		velocity: velocity.map_or(Vec3::ZERO, |velocity| velocity.0),
+		grounded: controller.is_some_and(CharacterController::is_grounded),
+	};
+	for (mut camera_transform, rig) in camera_query.iter_mut() {
+		if !first_person.is_synced() {
+			first_person.sync(&camera_transform, settings);
+		}
+		if let Some(delta) = input.look() {
+			first_person.look(delta, rig.sensitivity, settings);
+		}
+		*camera_transform = first_person.eye(body, settings, time.delta_seconds());
+	}
+}
+
+fn camera_following(
+	mut camera_query: PrimaryCameraQuery,
+	target_transform: &Transform,
+	input: CameraInput,
+	time: Res<Time>,
+) {
+	for (mut camera_transform, mut rig) in camera_query.iter_mut() {
+		if !rig.is_following() {
+			rig.sync(&camera_transform, target_transform.translation);
+		}
+		if let Some(delta) = input.look() {
+			rig.look(delta);
+		}
+		let scroll = input.scroll * rig.zoom_sensitivity;
+		rig.zoom(input.touch.zoom + scroll);
+		rig.follow(target_transform.translation, time.delta_seconds());
+		*camera_transform = rig.transform();
+	}
+}
+
+/* swings behind the character facing the target, pulling back far enough to frame both */
+fn camera_lock_on(
+	mut camera_query: PrimaryCameraQuery,
+	character_transform: &Transform,
+	target_transform: &Transform,
+	settings: &LockOnSettings,
+	input: CameraInput,
+	time: Res<Time>,
+) {
+	let height = Vec3::Y * settings.height;
+	let (character, target) = (character_transform.translation + height, target_transform.translation + height);
+	let separation = (target - character).with_y(0.);
+	let focus = character.lerp(target, 0.5);
+	for (mut camera_transform, mut rig) in camera_query.iter_mut() {
+		if !rig.is_following() {
+			rig.sync(&camera_transform, focus);
+		}
+		/* the target owns the yaw, looking only tilts */
+		if let Some(delta) = input.look() {
+			rig.look(delta.with_x(0.));
+		}
+		if separation != Vec3::ZERO {
+			let yaw = (-separation.x).atan2(-separation.z);
+			let delta = (yaw - rig.yaw + PI).rem_euclid(TAU) - PI;
+			rig.yaw += delta * (1. - (-settings.turn_speed * time.delta_seconds()).exp());
+		}
+		let scroll = input.scroll * rig.zoom_sensitivity;
+		rig.zoom(input.touch.zoom + scroll);
+		rig.follow(focus, time.delta_seconds());
+		let distance = rig.distance.max(separation.length() * settings.framing);
+		*camera_transform = rig.transform_at(distance);
+	}
+}
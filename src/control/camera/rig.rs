@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+/* spring integration substep, keeps the follow stable under long frames */
+const MAX_SUBSTEP: f32 = 1. / 120.;
+
+/* orbit state of a third person camera, the camera transform is derived from it every frame */
+#[derive(Component, Clone, Debug)]
+pub struct CameraRig {
+	pub yaw: f32,
+	pub pitch: f32,
+	pub distance: f32,
+	pub min_pitch: f32,
+	pub max_pitch: f32,
+	pub min_distance: f32,
+	pub max_distance: f32,
+	/* radians per pixel of mouse or touch motion */
+	pub sensitivity: f32,
+	/* distance per scroll line */
+	pub zoom_sensitivity: f32,
+	/* spring the focus point follows the target with */
+	pub stiffness: f32,
+	/* 1 is critically damped, below overshoots */
+	pub damping: f32,
+	/* seconds of target velocity the focus leads by, negative values lag behind */
+	pub lead: f32,
+	focus: Option<Vec3>,
+	focus_velocity: Vec3,
+	previous_target: Option<Vec3>,
+}
+
+impl Default for CameraRig {
+	fn default() -> Self {
+		Self {
+			yaw: 0.,
+			pitch: -0.3,
+			distance: 5.,
+			min_pitch: -1.4,
+			max_pitch: 1.2,
+			min_distance: 1.,
+			max_distance: 20.,
+			sensitivity: 0.004,
+			zoom_sensitivity: 0.5,
+			stiffness: 60.,
+			damping: 1.,
+			lead: 0.1,
+			focus: None,
+			focus_velocity: Vec3::ZERO,
+			previous_target: None,
+		}
+	}
+}
+
+impl CameraRig {
+	pub fn look(&mut self, delta: Vec2) {
+		self.yaw -= delta.x * self.sensitivity;
+		self.pitch = (self.pitch - delta.y * self.sensitivity).clamp(self.min_pitch, self.max_pitch);
+	}
+	/* positive zoom moves towards the focus */
+	pub fn zoom(&mut self, amount: f32) {
+		self.distance = (self.distance - amount).clamp(self.min_distance, self.max_distance);
+	}
+	/* picks up yaw, pitch and distance from wherever the camera currently is */
+	pub fn sync(&mut self, camera_transform: &Transform, focus: Vec3) {
+		let offset = camera_transform.translation - focus;
+		let distance = offset.length();
+		if distance > f32::EPSILON {
+			self.yaw = offset.x.atan2(offset.z);
+			self.pitch = (-offset.y / distance).asin().clamp(self.min_pitch, self.max_pitch);
+		}
+		self.distance = distance.clamp(self.min_distance, self.max_distance);
+		self.focus = Some(focus);
+		self.focus_velocity = Vec3::ZERO;
+		self.previous_target = Some(focus);
+	}
+	/* lets the next follow resync from the camera, for when another mode moved it */
+	pub fn release(&mut self) {
+		self.focus = None;
+		self.previous_target = None;
+	}
+	pub fn is_following(&self) -> bool {
+		self.focus.is_some()
+	}
+	/* springs the focus towards the target, leading it by its velocity */
+	pub fn follow(&mut self, target: Vec3, delta_seconds: f32) -> Vec3 {
+		if delta_seconds <= 0. {
+			return self.focus.unwrap_or(target);
+		}
+		let velocity = self.previous_target.map_or(Vec3::ZERO, |previous| (target - previous) / delta_seconds);
+		self.previous_target = Some(target);
+		let goal = target + velocity * self.lead;
+		let mut focus = self.focus.unwrap_or(target);
+		let steps = (delta_seconds / MAX_SUBSTEP).ceil().max(1.);
+		let step = delta_seconds / steps;
+		for _ in 0..steps as usize {
+			let acceleration = (goal - focus) * self.stiffness - self.focus_velocity * (2. * self.damping * self.stiffness.sqrt());
+			self.focus_velocity += acceleration * step;
+			focus += self.focus_velocity * step;
+		}
+		self.focus = Some(focus);
+		focus
+	}
+	pub fn rotation(&self) -> Quat {
+		Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.)
+	}
+	pub fn transform(&self) -> Transform {
+		self.transform_at(self.distance)
+	}
+	/* overrides the distance without touching the zoom the player chose */
+	pub fn transform_at(&self, distance: f32) -> Transform {
+		let focus = self.focus.unwrap_or_default();
+		Transform::from_translation(focus + self.rotation() * Vec3::Z * distance)
+			.looking_at(focus, Vec3::Y)
+	}
+}
@@ -0,0 +1,130 @@
+use super::super::{
+	Controlling,
+	Player,
+	PlayerCamera
+};
+
+use bevy::prelude::*;
+
+use character::{
+	StateChanged,
+	CharacterState,
+	GroundedState
+};
+
+/* landing faster than this shakes the camera, gaining 0.08 trauma per extra meter per second */
+const LANDING_SPEED_THRESHOLD: f32 = 6.;
+const LANDING_TRAUMA_PER_SPEED: f32 = 0.08;
+
+/* adds trauma to a player's camera, or to every camera without a player */
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CameraShake {
+	pub trauma: f32,
+	pub player: Option<Player>,
+}
+
+/* trauma based shake layered on top of whatever placed the camera this frame */
+#[derive(Component, Clone, Debug)]
+pub struct Shake {
+	/* 0 to 1, the shake grows with its square */
+	pub trauma: f32,
+	/* trauma lost per second */
+	pub decay: f32,
+	pub max_offset: Vec3,
+	/* yaw, pitch and roll in radians */
+	pub max_angle: Vec3,
+	pub frequency: f32,
+	elapsed: f32,
+	/* offset applied last frame, taken off again before the camera modes run */
+	applied: Option<(Vec3, Quat)>,
+}
+
+impl Default for Shake {
+	fn default() -> Self {
+		Self {
+			trauma: 0.,
+			decay: 1.,
+			max_offset: Vec3::new(0.15, 0.15, 0.05),
+			max_angle: Vec3::new(0.05, 0.05, 0.1),
+			frequency: 15.,
+			elapsed: 0.,
+			applied: None,
+		}
+	}
+}
+
+impl Shake {
+	pub fn add_trauma(&mut self, trauma: f32) {
+		self.trauma = (self.trauma + trauma).clamp(0., 1.);
+	}
+}
+
+/* smooth deterministic noise in -1 to 1, replays shake identically */
+fn noise(seed: f32, t: f32) -> f32 {
+	0.5 * (t + seed).sin() + 0.3 * (2.3 * t + 1.7 * seed).sin() + 0.2 * (4.1 * t + 2.9 * seed).sin()
+}
+
+pub(in crate::control) fn remove_camera_shake(
+	mut camera_query: Query<(&mut Transform, &mut Shake), With<Camera>>,
+) {
+	for (mut camera_transform, mut shake) in camera_query.iter_mut() {
+		if let Some((offset, rotation)) = shake.applied.take() {
+			camera_transform.translation -= offset;
+			camera_transform.rotation *= rotation.inverse();
+		}
+	}
+}
+
+pub(in crate::control) fn apply_camera_shake(
+	mut shake_events: EventReader<CameraShake>,
+	mut camera_query: Query<(&mut Transform, &mut Shake, Option<&PlayerCamera>), With<Camera>>,
+	time: Res<Time>,
+) {
+	let events: Vec<CameraShake> = shake_events.read().copied().collect();
+	for (mut camera_transform, mut shake, camera) in camera_query.iter_mut() {
+		for event in events.iter() {
+			if event.player.map_or(true, |player| camera.is_some_and(|camera| camera.0 == player)) {
+				shake.add_trauma(event.trauma);
+			}
+		}
+		shake.trauma = (shake.trauma - shake.decay * time.delta_seconds()).max(0.);
+		if shake.trauma == 0. {
+			shake.elapsed = 0.;
+			continue;
+		}
+		shake.elapsed += time.delta_seconds();
+		let amount = shake.trauma * shake.trauma;
+		let t = shake.elapsed * shake.frequency;
+		let offset = shake.max_offset * Vec3::new(noise(1., t), noise(2., t), noise(3., t)) * amount;
+		let angle = shake.max_angle * Vec3::new(noise(4., t), noise(5., t), noise(6., t)) * amount;
+		let rotation = Quat::from_euler(EulerRot::YXZ, angle.x, angle.y, angle.z);
+		/* offset in camera space so it reads the same from any angle */
+		let offset = camera_transform.rotation * offset;
+		camera_transform.translation += offset;
+		camera_transform.rotation *= rotation;
+		shake.applied = Some((offset, rotation));
+	}
+}
+
+/* hard landings of controlled characters shake their player's camera, the event carries the impact velocity */
+pub(in crate::control) fn shake_on_landing(
+	mut state_events: EventReader<StateChanged>,
+	character_query: Query<&Controlling>,
+	mut shake_events: EventWriter<CameraShake>,
+) {
+	for event in state_events.read() {
+		if event.to != CharacterState::Grounded(GroundedState::Landing) {
+			continue;
+		}
+		let Ok(controlling) = character_query.get(event.entity) else {
+			continue;
+		};
+		let speed = -event.velocity.y - LANDING_SPEED_THRESHOLD;
+		if speed > 0. {
+			shake_events.send(CameraShake {
+				trauma: speed * LANDING_TRAUMA_PER_SPEED,
+				player: Some(controlling.0),
+			});
+		}
+	}
+}
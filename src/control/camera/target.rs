@@ -0,0 +1,77 @@
+use super::{
+	CameraMode,
+	super::SplitCamera,
+};
+
+use bevy::prelude::*;
+
+/* sent when the entity a camera mode follows stops existing */
+#[derive(Event, Clone, Debug)]
+pub struct CameraTargetLost {
+	pub mode: CameraMode,
+	pub entity: Entity,
+	/* none when the target was never seen */
+	pub last_position: Option<Vec3>,
+}
+
+/* mode the camera switches to once its target is lost, targets of the fallback are validated as well */
+#[derive(Resource, Clone, Debug)]
+pub struct CameraFallback(pub CameraMode);
+
+impl Default for CameraFallback {
+	fn default() -> Self {
+		Self(CameraMode::FreePerspective)
+	}
+}
+
+impl CameraMode {
+	pub fn target(&self) -> Option<Entity> {
+		match *self {
+			Self::FreeFollowing(entity) | Self::Perspective(entity) | Self::Following(entity) | Self::LockOn(entity, _) => Some(entity),
+			Self::Tracked | Self::FreePerspective | Self::Cinematic => None,
+		}
+	}
+}
+
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub(in crate::control) struct LastTargetPosition(Option<Vec3>);
+
+pub(in crate::control) fn validate_camera_target(
+	camera_state: Res<State<CameraMode>>,
+	mut next_camera_state: ResMut<NextState<CameraMode>>,
+	fallback: Res<CameraFallback>,
+	target_query: Query<&GlobalTransform>,
+	mut camera_query: Query<&mut Transform, (With<Camera>, Without<SplitCamera>)>,
+	mut last_position: ResMut<LastTargetPosition>,
+	mut lost_events: EventWriter<CameraTargetLost>,
+) {
+	let mode = camera_state.get();
+	let Some(entity) = mode.target() else {
+		last_position.0 = None;
+		return;
+	};
+	if let Ok(target_transform) = target_query.get(entity) {
+		last_position.0 = Some(target_transform.translation());
+		return;
+	}
+
+	let fallback = match fallback.0.target() {
+		Some(target) if !target_query.contains(target) => CameraMode::FreePerspective,
+		_ => fallback.0.clone(),
+	};
+	/* the camera stays put, facing where the target was last seen */
+	let position = last_position.0.take();
+	if let Some(position) = position {
+		for mut camera_transform in camera_query.iter_mut() {
+			if camera_transform.translation != position {
+				camera_transform.look_at(position, Vec3::Y);
+			}
+		}
+	}
+	lost_events.send(CameraTargetLost {
+		mode: mode.clone(),
+		entity,
+		last_position: position,
+	});
+	next_camera_state.set(fallback);
+}
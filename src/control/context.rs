@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use serde::{
+	Serialize,
+	Deserialize
+};
+
+/* selects the binding table input is read from, contexts without a table swallow all input */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum InputContext {
+	#[default]
+	Gameplay,
+	Menu,
+	Editor,
+	FreeCamera,
+}
+
+/* the top of the stack is the active context, gameplay is always at the bottom */
+#[derive(Resource, Debug)]
+pub struct InputContextStack(Vec<InputContext>);
+
+impl Default for InputContextStack {
+	fn default() -> Self {
+		Self(vec![InputContext::Gameplay])
+	}
+}
+
+impl InputContextStack {
+	pub fn current(&self) -> InputContext {
+		self.0.last().copied().unwrap_or_default()
+	}
+	pub fn push(&mut self, context: InputContext) {
+		self.0.push(context);
+	}
+	/* removes the topmost occurrence of the context, leaving gameplay in place */
+	pub fn pop(&mut self, context: InputContext) -> bool {
+		match self.0.iter().rposition(|&other| other == context) {
+			Some(0) | None => false,
+			Some(index) => {
+				self.0.remove(index);
+				true
+			},
+		}
+	}
+	pub fn contains(&self, context: InputContext) -> bool {
+		self.0.contains(&context)
+	}
+}
+
+pub(super) fn push_free_camera_context(mut context_stack: ResMut<InputContextStack>) {
+	context_stack.push(InputContext::FreeCamera);
+}
+
+pub(super) fn pop_free_camera_context(mut context_stack: ResMut<InputContextStack>) {
+	context_stack.pop(InputContext::FreeCamera);
+}
@@ -1,10 +1,22 @@
 mod bindings;
 mod camera;
 mod actions;
+mod player;
+mod touch;
+mod trigger;
+mod context;
+mod recorder;
+mod possession;
 
-use bindings::*;
+pub use bindings::*;
 pub use camera::*;
 use actions::*;
+pub use player::*;
+pub use touch::*;
+pub use trigger::*;
+pub use context::*;
+pub use recorder::*;
+pub use possession::*;
 
 use bevy::prelude::*;
 
@@ -16,22 +28,70 @@ pub struct ControlPlugin;
 
 impl Plugin for ControlPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_plugins(CharacterControllerPlugin)
+		app.add_plugins((CharacterControllerPlugin, LockOnPlugin, RecorderPlugin))
 			.insert_resource(persistent_bindings())
-			.add_event::<CharacterAction>()
+			.add_event::<PlayerAction>()
+			.add_event::<Possess>()
+			.add_event::<CameraTargetLost>()
+			.add_event::<PlayCinematic>()
+			.add_event::<CameraShake>()
+			.init_asset::<CameraSequence>()
+			.init_asset_loader::<CameraSequenceLoader>()
+			.init_resource::<GamepadAssignments>()
+			.init_resource::<InputContextStack>()
+			.init_resource::<TriggerState>()
+			.init_resource::<TouchControls>()
+			.init_resource::<TouchCameraInput>()
+			.init_resource::<CameraFallback>()
+			.init_resource::<LastTargetPosition>()
+			.init_resource::<FirstPersonSettings>()
+			.init_resource::<FirstPerson>()
+			.init_resource::<CameraRetargetSettings>()
 			.init_state::<CameraMode>()
+			.add_systems(Startup, spawn_touch_controls)
+			.add_systems(OnEnter(CameraMode::FreePerspective), push_free_camera_context)
+			.add_systems(OnExit(CameraMode::FreePerspective), pop_free_camera_context)
 			.add_systems(Update, (
 				init_character_animation_player,
-				process_input.before(process_actions),
+				assign_gamepads.before(process_input),
+				update_players.after(assign_gamepads),
+				follow_split_cameras,
+				update_viewports,
+				process_input.in_set(InputSet).before(process_actions),
+				process_touch_input.before(process_actions).before(camera_control),
+				update_touch_joystick.after(process_touch_input),
 				process_actions,
+				release_uncontrolled,
+				process_hotkeys.before(possess),
+				lock_on_hotkeys,
+				possess.before(process_actions),
+				retarget_camera.after(camera_control),
+				animate_state_changes,
 				camera_control,
 			)
+		)
+		.add_systems(Update, (
+				validate_camera_target.before(camera_control).before(retarget_camera),
+				play_cinematic.before(camera_cinematic),
+				camera_cinematic.run_if(in_state(CameraMode::Cinematic)).after(camera_control).before(camera_collision),
+				restore_camera_collision.before(camera_control).before(follow_split_cameras),
+				camera_collision.after(camera_control).after(retarget_camera).after(follow_split_cameras),
+				fade_occluders.after(camera_collision),
+				remove_camera_shake.before(restore_camera_collision).before(validate_camera_target).before(replay_input),
+				apply_camera_shake.after(camera_collision).after(camera_cinematic).after(record_input),
+				shake_on_landing.before(apply_camera_shake),
+				update_first_person_body.before(camera_control),
+			)
 		);
 	}
 }
 
-#[derive(Component)]
-pub struct Controlling;
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Controlling(pub Player);
+
+/* where bindings turn into actions, menus capturing input run after it so the captured press is never read as gameplay */
+#[derive(SystemSet, Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub struct InputSet;
 
 fn persistent_bindings() -> Persistent<Bindings> {
 	Persistent::<Bindings>::builder()
@@ -40,18 +100,45 @@ fn persistent_bindings() -> Persistent<Bindings> {
 		.path("bindings.toml")
 		.default({
 			let mut bindings = Bindings::default();
-			bindings.keys.insert(KeyCode::KeyW, CharacterAction::Forward);
-			bindings.keys.insert(KeyCode::KeyS, CharacterAction::Backward);
-			bindings.keys.insert(KeyCode::KeyA, CharacterAction::Left);
-			bindings.keys.insert(KeyCode::KeyD, CharacterAction::Right);
-			bindings.keys.insert(KeyCode::ArrowUp, CharacterAction::Forward);
-			bindings.keys.insert(KeyCode::ArrowDown, CharacterAction::Backward);
-			bindings.keys.insert(KeyCode::ArrowLeft, CharacterAction::Left);
-			bindings.keys.insert(KeyCode::ArrowRight, CharacterAction::Right);
-			bindings.keys.insert(KeyCode::ShiftLeft, CharacterAction::Sprint);
-			bindings.keys.insert(KeyCode::Space, CharacterAction::Jump);
-
-			// bindings.mouse.insert(MouseButton::Right, CharacterAction::Jump);
+			bindings.contexts.insert(InputContext::Menu, BindingTable::default());
+			bindings.contexts.insert(InputContext::Editor, BindingTable::default());
+			bindings.contexts.insert(InputContext::FreeCamera, BindingTable::default());
+
+			bindings.gameplay.keys.insert(KeyCode::KeyW, CharacterAction::Forward.into());
+			bindings.gameplay.keys.insert(KeyCode::KeyS, CharacterAction::Backward.into());
+			bindings.gameplay.keys.insert(KeyCode::KeyA, CharacterAction::Left.into());
+			bindings.gameplay.keys.insert(KeyCode::KeyD, CharacterAction::Right.into());
+			bindings.gameplay.keys.insert(KeyCode::ArrowUp, CharacterAction::Forward.into());
+			bindings.gameplay.keys.insert(KeyCode::ArrowDown, CharacterAction::Backward.into());
+			bindings.gameplay.keys.insert(KeyCode::ArrowLeft, CharacterAction::Left.into());
+			bindings.gameplay.keys.insert(KeyCode::ArrowRight, CharacterAction::Right.into());
+			bindings.gameplay.keys.insert(KeyCode::ShiftLeft, CharacterAction::Sprint.into());
+			bindings.gameplay.keys.insert(KeyCode::Space, CharacterAction::Jump.into());
+			bindings.gameplay.keys.insert(KeyCode::ControlLeft, CharacterAction::Crouch.into());
+			bindings.gameplay.keys.insert(KeyCode::KeyZ, CharacterAction::Prone.into());
+
+			// bindings.gameplay.mouse.insert(MouseButton::Right, CharacterAction::Jump.into());
+
+			bindings.hotkeys.insert(KeyCode::Tab, Hotkey::PossessNext);
+			bindings.hotkeys.insert(KeyCode::KeyQ, Hotkey::LockOn);
+			bindings.hotkeys.insert(KeyCode::KeyE, Hotkey::CycleLockOn);
+
+			bindings.players = PlayerProfile::defaults();
+
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::South, CharacterAction::Jump.into());
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::LeftThumb, ActionBinding::new(CharacterAction::Sprint, Trigger::Toggle));
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::East, CharacterAction::Crouch.into());
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::North, CharacterAction::Prone.into());
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::DPadUp, CharacterAction::Forward.into());
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::DPadDown, CharacterAction::Backward.into());
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::DPadLeft, CharacterAction::Left.into());
+			bindings.gameplay.gamepad.buttons.insert(GamepadButtonType::DPadRight, CharacterAction::Right.into());
+			bindings.gameplay.gamepad.axes.insert(GamepadAxisType::LeftStickX, AxisBinding::MoveX);
+			bindings.gameplay.gamepad.axes.insert(GamepadAxisType::LeftStickY, AxisBinding::MoveY);
+			bindings.gameplay.gamepad.axes.insert(GamepadAxisType::RightZ, AxisBinding::Positive {
+				action: CharacterAction::Sprint,
+				deadzone: 0.3,
+			});
 			bindings
 		})
 		.revertible(true)
@@ -0,0 +1,237 @@
+use super::{
+	Controlling,
+	CameraCollision,
+	LockOnSettings,
+	LockOnTarget,
+	Shake,
+	FIRST_PERSON_HIDDEN_LAYER,
+	bindings::Bindings,
+};
+
+use bevy::{
+	prelude::*,
+	render::{
+		camera::Viewport,
+		view::RenderLayers,
+	},
+	utils::HashMap,
+	window::PrimaryWindow,
+};
+use serde::{
+	Serialize,
+	Deserialize
+};
+use bevy_persistent::prelude::*;
+
+use character::{
+	CharacterAction,
+	CharacterController
+};
+
+const SPLIT_CAMERA_DISTANCE: f32 = 4.;
+const SPLIT_CAMERA_HEIGHT: f32 = 2.;
+const SPLIT_CAMERA_SPEED: f32 = 5.;
+
+/* index into the player profiles of the bindings file */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Player(pub usize);
+
+#[derive(Event, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PlayerAction {
+	pub player: Player,
+	pub action: CharacterAction,
+	/* set for Move, x is right and y is forward with a magnitude up to 1 */
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub move_axis: Option<Vec2>,
+}
+
+impl PlayerAction {
+	pub fn new(player: Player, action: CharacterAction) -> Self {
+		Self {
+			player,
+			action,
+			move_axis: None,
+		}
+	}
+	pub fn movement(player: Player, axis: Vec2) -> Self {
+		Self {
+			player,
+			action: CharacterAction::Move,
+			move_axis: Some(axis),
+		}
+	}
+}
+
+/* the camera rendering a player's view, the first player's camera follows the camera mode */
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PlayerCamera(pub Player);
+
+/* cameras spawned for players joining after the first, they only trail their character, frame its lock on
+target and avoid walls, the rig, first person, free and cinematic modes of CameraMode stay with the first player */
+#[derive(Component)]
+pub struct SplitCamera;
+
+/* which player each connected gamepad drives */
+#[derive(Resource, Default, Debug)]
+pub struct GamepadAssignments(HashMap<Gamepad, Player>);
+
+impl GamepadAssignments {
+	pub fn player(&self, gamepad: Gamepad) -> Option<Player> {
+		self.0.get(&gamepad).copied()
+	}
+	pub fn gamepads(&self, player: Player) -> impl Iterator<Item = Gamepad> + '_ {
+		self.0.iter().filter(move |(_, &assigned)| assigned == player).map(|(&gamepad, _)| gamepad)
+	}
+	pub fn has_gamepad(&self, player: Player) -> bool {
+		self.0.values().any(|&assigned| assigned == player)
+	}
+}
+
+/* keeps assignments in sync with connected gamepads, so gamepads can be plugged in and out at any time */
+pub(super) fn assign_gamepads(
+	mut assignments: ResMut<GamepadAssignments>,
+	gamepads: Res<Gamepads>,
+	bindings: Res<Persistent<Bindings>>,
+) {
+	assignments.0.retain(|&gamepad, _| gamepads.contains(gamepad));
+	for gamepad in gamepads.iter() {
+		if assignments.0.contains_key(&gamepad) {
+			continue;
+		}
+		let free = bindings.get().players.iter().enumerate()
+			.find(|&(index, profile)| profile.gamepad && !assignments.has_gamepad(Player(index)));
+		if let Some((index, _)) = free {
+			assignments.0.insert(gamepad, Player(index));
+		}
+	}
+}
+
+/* players with an input device join with a free character and a camera, and leave when they lose it */
+pub(super) fn update_players(
+	mut commands: Commands,
+	bindings: Res<Persistent<Bindings>>,
+	assignments: Res<GamepadAssignments>,
+	controlling_query: Query<(Entity, &Controlling)>,
+	free_query: Query<Entity, (With<CharacterController>, Without<Controlling>)>,
+	camera_query: Query<(Entity, &PlayerCamera)>,
+	transform_query: Query<&GlobalTransform>,
+) {
+	let active: Vec<Player> = bindings.get().players.iter().enumerate()
+		.map(|(index, profile)| (Player(index), profile))
+		.filter(|&(player, profile)| player == Player(0) || profile.keyboard || profile.touch || assignments.has_gamepad(player))
+		.map(|(player, _)| player)
+		.collect();
+
+	for (entity, controlling) in controlling_query.iter() {
+		if controlling.0 != Player(0) && !active.contains(&controlling.0) {
+			commands.entity(entity).remove::<Controlling>();
+		}
+	}
+	for (entity, camera) in camera_query.iter() {
+		if camera.0 != Player(0) && !active.contains(&camera.0) {
+			commands.entity(entity).despawn_recursive();
+		}
+	}
+
+	let mut free = free_query.iter();
+	for &player in active.iter() {
+		let character = match controlling_query.iter().find(|(_, controlling)| controlling.0 == player) {
+			Some((entity, _)) => Some(entity),
+			None => free.next().inspect(|&entity| {
+				commands.entity(entity).insert(Controlling(player));
+			}),
+		};
+		if camera_query.iter().any(|(_, camera)| camera.0 == player) {
+			continue;
+		}
+		let focus = character
+			.and_then(|entity| transform_query.get(entity).ok())
+			.map(|transform| transform.translation())
+			.unwrap_or_default();
+		commands.spawn((
+			Camera3dBundle {
+				camera: Camera {
+					order: player.0 as isize,
+					..default()
+				},
+				transform: Transform::from_translation(focus + Vec3::new(0., SPLIT_CAMERA_HEIGHT, SPLIT_CAMERA_DISTANCE))
+					.looking_at(focus, Vec3::Y),
+				..default()
+			},
+			PlayerCamera(player),
+			SplitCamera,
+			CameraCollision::default(),
+			Shake::default(),
+			/* sees the head the first player's first person view hides */
+			RenderLayers::from_layers(&[0, FIRST_PERSON_HIDDEN_LAYER]),
+		));
+	}
+}
+
+/* split cameras trail behind their player's character, framing its lock on target as well */
+pub(super) fn follow_split_cameras(
+	mut camera_query: Query<(&mut Transform, &PlayerCamera, Option<&LockOnTarget>), (With<Camera>, With<SplitCamera>)>,
+	character_query: Query<(&Transform, &Controlling), Without<Camera>>,
+	target_query: Query<&GlobalTransform>,
+	lock_on_settings: Res<LockOnSettings>,
+	time: Res<Time>,
+) {
+	for (mut camera_transform, camera, lock_on_target) in camera_query.iter_mut() {
+		let Some((target_transform, _)) = character_query.iter().find(|(_, controlling)| controlling.0 == camera.0) else {
+			continue;
+		};
+		let character = target_transform.translation + Vec3::Y;
+		let lock_on = lock_on_target
+			.and_then(|lock| target_query.get(lock.target).ok())
+			.map(|target| target.translation() + Vec3::Y * lock_on_settings.height);
+		let (focus, back, distance) = match lock_on {
+			/* behind the character, facing the target */
+			Some(target) => {
+				let separation = (target - character).with_y(0.);
+				(
+					character.lerp(target, 0.5),
+					(-separation).try_normalize().unwrap_or(Vec3::Z),
+					SPLIT_CAMERA_DISTANCE.max(separation.length() * lock_on_settings.framing),
+				)
+			},
+			None => (
+				character,
+				(camera_transform.translation - character).with_y(0.).try_normalize().unwrap_or(Vec3::Z),
+				SPLIT_CAMERA_DISTANCE,
+			),
+		};
+		let destination = focus + back * distance + Vec3::Y * SPLIT_CAMERA_HEIGHT;
+		let t = 1. - (-SPLIT_CAMERA_SPEED * time.delta_seconds()).exp();
+		camera_transform.translation = camera_transform.translation.lerp(destination, t);
+		camera_transform.look_at(focus, Vec3::Y);
+	}
+}
+
+/* side by side for two players, a grid beyond that */
+pub(super) fn update_viewports(
+	window_query: Query<&Window, With<PrimaryWindow>>,
+	mut camera_query: Query<(&mut Camera, &PlayerCamera)>,
+) {
+	let Ok(window) = window_query.get_single() else {
+		return;
+	};
+	let size = window.physical_size();
+	let mut cameras: Vec<_> = camera_query.iter_mut().collect();
+	cameras.sort_by_key(|(_, player_camera)| player_camera.0);
+	let count = cameras.len() as u32;
+	let columns = count.clamp(1, 2);
+	let rows = count.div_ceil(2).max(1);
+	let cell = UVec2::new(size.x / columns, size.y / rows);
+	for (index, (camera, _)) in cameras.iter_mut().enumerate() {
+		let index = index as u32;
+		let viewport = (count > 1).then(|| Viewport {
+			physical_position: UVec2::new(index % columns, index / columns) * cell,
+			physical_size: cell,
+			..default()
+		});
+		let current = camera.viewport.as_ref().map(|viewport| (viewport.physical_position, viewport.physical_size));
+		if current != viewport.as_ref().map(|viewport| (viewport.physical_position, viewport.physical_size)) {
+			camera.viewport = viewport;
+		}
+	}
+}
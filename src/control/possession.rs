@@ -0,0 +1,198 @@
+use super::{
+	Controlling,
+	Player,
+	SplitCamera,
+	CameraMode,
+	FirstPersonSettings,
+	InputContext,
+	InputContextStack,
+	bindings::Bindings,
+};
+
+use bevy::prelude::*;
+use serde::{
+	Serialize,
+	Deserialize
+};
+use bevy_persistent::prelude::*;
+use bevy_egui::EguiContexts;
+
+use character::CharacterController;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum Hotkey {
+	PossessNext,
+	PossessPrevious,
+	LockOn,
+	CycleLockOn,
+}
+
+/* moves the player onto the character, characters held by other players are left alone */
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Possess {
+	pub player: Player,
+	pub entity: Entity,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct CameraRetargetSettings {
+	/* seconds the camera takes to ease onto the new character */
+	pub duration: f32,
+}
+
+impl Default for CameraRetargetSettings {
+	fn default() -> Self {
+		Self {
+			duration: 0.6,
+		}
+	}
+}
+
+/* eases the camera from where it was towards its new target */
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct CameraRetarget {
+	pub duration: f32,
+	from: Transform,
+	offset: Vec3,
+	elapsed: f32,
+}
+
+/* hotkeys belong to the first keyboard player, and only while playing */
+pub(super) fn hotkey_player(
+	bindings: &Bindings,
+	context_stack: &InputContextStack,
+	contexts: &mut EguiContexts,
+) -> Option<Player> {
+	if context_stack.current() != InputContext::Gameplay || contexts.ctx_mut().wants_keyboard_input() {
+		return None;
+	}
+	bindings.players.iter().position(|profile| profile.keyboard).map(Player)
+}
+
+pub(super) fn process_hotkeys(
+	key_input: Res<ButtonInput<KeyCode>>,
+	bindings: Res<Persistent<Bindings>>,
+	context_stack: Res<InputContextStack>,
+	mut contexts: EguiContexts,
+	character_query: Query<(Entity, Option<&Controlling>), With<CharacterController>>,
+	mut possess_events: EventWriter<Possess>,
+) {
+	let Some(player) = hotkey_player(bindings.get(), &context_stack, &mut contexts) else {
+		return;
+	};
+	for key in key_input.get_just_pressed() {
+		let step = match bindings.get().hotkeys.get(key) {
+			Some(Hotkey::PossessNext) => 1,
+			Some(Hotkey::PossessPrevious) => -1,
+			/* handled by the lock on camera */
+			Some(Hotkey::LockOn | Hotkey::CycleLockOn) | None => continue,
+		};
+		/* characters held by other players are skipped */
+		let mut characters: Vec<(Entity, bool)> = character_query.iter()
+			.filter(|&(_, controlling)| controlling.map_or(true, |controlling| controlling.0 == player))
+			.map(|(entity, controlling)| (entity, controlling.is_some()))
+			.collect();
+		if characters.is_empty() {
+			continue;
+		}
+		characters.sort_by_key(|&(entity, _)| entity);
+		let current = characters.iter().position(|&(_, controlling)| controlling).unwrap_or(0) as isize;
+		let next = (current + step).rem_euclid(characters.len() as isize) as usize;
+		possess_events.send(Possess {
+			player,
+			entity: characters[next].0,
+		});
+	}
+}
+
+pub(super) fn possess(
+	mut commands: Commands,
+	mut possess_events: EventReader<Possess>,
+	controlling_query: Query<(Entity, &Controlling)>,
+	character_query: Query<Has<Controlling>, With<CharacterController>>,
+	transform_query: Query<&GlobalTransform>,
+	camera_query: Query<&Transform, (With<Camera>, Without<SplitCamera>)>,
+	camera_state: Res<State<CameraMode>>,
+	mut next_camera_state: ResMut<NextState<CameraMode>>,
+	retarget_settings: Res<CameraRetargetSettings>,
+) {
+	let Some(&Possess { player, entity: target }) = possess_events.read().last() else {
+		return;
+	};
+	if !matches!(character_query.get(target), Ok(false)) {
+		return;
+	}
+	let mut previous = None;
+	for (entity, _) in controlling_query.iter().filter(|(_, controlling)| controlling.0 == player) {
+		commands.entity(entity).remove::<Controlling>();
+		previous = Some(entity);
+	}
+	commands.entity(target).insert(Controlling(player));
+
+	/* the camera mode drives the first player's camera */
+	if player != Player(0) {
+		return;
+	}
+
+	let retargeted = match *camera_state.get() {
+		CameraMode::Following(entity) if Some(entity) == previous => CameraMode::Following(target),
+		CameraMode::Perspective(entity) if Some(entity) == previous => CameraMode::Perspective(target),
+		CameraMode::FreeFollowing(entity) if Some(entity) == previous => CameraMode::FreeFollowing(target),
+		/* the new character picks its own lock */
+		CameraMode::LockOn(entity, _) if Some(entity) == previous => CameraMode::Following(target),
+		_ => return,
+	};
+	next_camera_state.set(retargeted);
+	let (Some(previous), Some(&camera_transform)) = (previous, camera_query.iter().next()) else {
+		return;
+	};
+	if let Ok(previous_transform) = transform_query.get(previous) {
+		commands.insert_resource(CameraRetarget {
+			duration: retarget_settings.duration,
+			from: camera_transform,
+			offset: camera_transform.translation - previous_transform.translation(),
+			elapsed: 0.,
+		});
+	}
+}
+
+/* runs after the camera modes so the blend has the final say */
+pub(super) fn retarget_camera(
+	mut commands: Commands,
+	retarget: Option<ResMut<CameraRetarget>>,
+	camera_state: Res<State<CameraMode>>,
+	transform_query: Query<&GlobalTransform>,
+	mut camera_query: Query<&mut Transform, (With<Camera>, Without<SplitCamera>)>,
+	first_person_settings: Res<FirstPersonSettings>,
+	time: Res<Time>,
+) {
+	let Some(mut retarget) = retarget else {
+		return;
+	};
+	let target = match *camera_state.get() {
+		CameraMode::Following(entity) | CameraMode::Perspective(entity) | CameraMode::FreeFollowing(entity) => entity,
+		_ => {
+			commands.remove_resource::<CameraRetarget>();
+			return;
+		},
+	};
+	let Ok(target_transform) = transform_query.get(target) else {
+		commands.remove_resource::<CameraRetarget>();
+		return;
+	};
+	retarget.elapsed += time.delta_seconds();
+	let t = (retarget.elapsed / retarget.duration).clamp(0., 1.);
+	let t = t * t * (3. - 2. * t);
+	let focus = target_transform.translation();
+	let destination = match *camera_state.get() {
+		CameraMode::Perspective(_) => Transform::from_translation(focus + Vec3::Y * first_person_settings.eye_height).with_rotation(retarget.from.rotation),
+		_ => Transform::from_translation(focus + retarget.offset).looking_at(focus, Vec3::Y),
+	};
+	for mut camera_transform in camera_query.iter_mut() {
+		camera_transform.translation = retarget.from.translation.lerp(destination.translation, t);
+		camera_transform.rotation = retarget.from.rotation.slerp(destination.rotation, t);
+	}
+	if t >= 1. {
+		commands.remove_resource::<CameraRetarget>();
+	}
+}
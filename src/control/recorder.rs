@@ -0,0 +1,199 @@
+use super::{
+	PlayerAction,
+	SplitCamera,
+	process_input,
+	process_touch_input,
+	process_actions,
+	camera_control,
+};
+
+use std::{
+	fs,
+	path::{
+		Path,
+		PathBuf
+	},
+	time::Duration,
+};
+
+use bevy::{
+	prelude::*,
+	time::{
+		TimeSystem,
+		TimeUpdateStrategy
+	},
+};
+use serde::{
+	Serialize,
+	Deserialize
+};
+
+pub const DEFAULT_RECORDING_PATH: &str = "recordings/latest.ron";
+const DEFAULT_TIMESTEP: f32 = 1. / 60.;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct InputFrame {
+	pub actions: Vec<PlayerAction>,
+	/* the first player's camera */
+	pub camera: Transform,
+}
+
+/* recorded and replayed under the same fixed timestep, so physics sees identical deltas */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputRecording {
+	pub timestep: f32,
+	pub frames: Vec<InputFrame>,
+}
+
+impl Default for InputRecording {
+	fn default() -> Self {
+		Self {
+			timestep: DEFAULT_TIMESTEP,
+			frames: Vec::new(),
+		}
+	}
+}
+
+impl InputRecording {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+		let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+		ron::from_str(&contents).map_err(|error| error.to_string())
+	}
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+		let path = path.as_ref();
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+		}
+		let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|error| error.to_string())?;
+		fs::write(path, contents).map_err(|error| error.to_string())
+	}
+	pub fn duration(&self) -> f32 {
+		self.frames.len() as f32 * self.timestep
+	}
+}
+
+/* frames are only captured or replayed once the fixed timestep drives the clock */
+#[derive(Resource, Default)]
+pub enum InputRecorder {
+	#[default]
+	Idle,
+	Recording {
+		path: PathBuf,
+		recording: InputRecording,
+		started: bool,
+	},
+	Replaying {
+		recording: InputRecording,
+		frame: usize,
+		started: bool,
+	},
+}
+
+impl InputRecorder {
+	pub fn is_recording(&self) -> bool {
+		matches!(self, Self::Recording { .. })
+	}
+	pub fn is_replaying(&self) -> bool {
+		matches!(self, Self::Replaying { .. })
+	}
+	pub fn start_recording(&mut self, path: impl Into<PathBuf>) {
+		*self = Self::Recording {
+			path: path.into(),
+			recording: InputRecording::default(),
+			started: false,
+		};
+	}
+	pub fn start_replay(&mut self, recording: InputRecording) {
+		*self = Self::Replaying {
+			recording,
+			frame: 0,
+			started: false,
+		};
+	}
+	/* saves a running recording, returning the path it was written to */
+	pub fn stop(&mut self) -> Result<Option<PathBuf>, String> {
+		match std::mem::take(self) {
+			Self::Recording { path, recording, .. } => recording.save(&path).map(|_| Some(path)),
+			_ => Ok(None),
+		}
+	}
+}
+
+/* the strategy in place before the recorder fixed the timestep */
+#[derive(Resource, Default)]
+struct SavedTimeStrategy(Option<TimeUpdateStrategy>);
+
+/* kept apart from the control plugin so replays can run headless */
+pub struct RecorderPlugin;
+
+impl Plugin for RecorderPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<PlayerAction>()
+			.init_resource::<InputRecorder>()
+			.init_resource::<SavedTimeStrategy>()
+			.add_systems(First, update_time_strategy.before(TimeSystem))
+			.add_systems(Update, (
+				record_input.after(process_input).after(process_touch_input).after(camera_control).before(process_actions),
+				replay_input.after(record_input).before(process_actions),
+			)
+		);
+	}
+}
+
+/* fixes the timestep before time advances, so the first captured frame already runs on it, and restores the previous strategy when idle */
+fn update_time_strategy(
+	mut recorder: ResMut<InputRecorder>,
+	mut strategy: ResMut<TimeUpdateStrategy>,
+	mut saved: ResMut<SavedTimeStrategy>,
+) {
+	match recorder.bypass_change_detection() {
+		InputRecorder::Recording { recording, started, .. } | InputRecorder::Replaying { recording, started, .. } => {
+			if !*started {
+				let fixed = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(recording.timestep));
+				let previous = std::mem::replace(strategy.as_mut(), fixed);
+				/* going straight from one recording to another keeps the original strategy */
+				saved.0.get_or_insert(previous);
+				*started = true;
+			}
+		},
+		InputRecorder::Idle => if let Some(previous) = saved.0.take() {
+			*strategy = previous;
+		},
+	}
+}
+
+pub(super) fn record_input(
+	mut recorder: ResMut<InputRecorder>,
+	mut action_events: EventReader<PlayerAction>,
+	camera_query: Query<&Transform, (With<Camera>, Without<SplitCamera>)>,
+) {
+	let InputRecorder::Recording { recording, started: true, .. } = recorder.bypass_change_detection() else {
+		action_events.clear();
+		return;
+	};
+	recording.frames.push(InputFrame {
+		actions: action_events.read().copied().collect(),
+		camera: camera_query.iter().next().copied().unwrap_or_default(),
+	});
+}
+
+/* replaces live input with the recorded frame */
+pub(super) fn replay_input(
+	mut recorder: ResMut<InputRecorder>,
+	mut action_events: ResMut<Events<PlayerAction>>,
+	mut camera_query: Query<&mut Transform, (With<Camera>, Without<SplitCamera>)>,
+) {
+	let InputRecorder::Replaying { recording, frame, started: true } = recorder.bypass_change_detection() else {
+		return;
+	};
+	let Some(input) = recording.frames.get(*frame) else {
+		*recorder = InputRecorder::Idle;
+		return;
+	};
+	*frame += 1;
+	action_events.clear();
+	action_events.send_batch(input.actions.iter().copied());
+	if let Some(mut camera_transform) = camera_query.iter_mut().next() {
+		*camera_transform = input.camera;
+	}
+}
@@ -0,0 +1,217 @@
+use super::{
+	TouchCameraInput,
+	InputContext,
+	InputContextStack,
+	Player,
+	PlayerAction,
+	bindings::Bindings,
+};
+
+use bevy::{
+	prelude::*,
+	input::touch::Touch,
+	window::PrimaryWindow,
+};
+use bevy_persistent::prelude::*;
+
+use character::CharacterAction;
+
+const JOYSTICK_COLOR: Color = Color::srgba(1., 1., 1., 0.15);
+const KNOB_COLOR: Color = Color::srgba(1., 1., 1., 0.4);
+const BUTTON_COLOR: Color = Color::srgba(1., 1., 1., 0.25);
+
+/* the left half of the screen spawns a floating joystick, the right half orbits and pinches the camera */
+#[derive(Resource)]
+pub struct TouchControls {
+	pub enable: bool,
+	/* sizes in logical pixels */
+	pub joystick_radius: f32,
+	pub knob_radius: f32,
+	pub button_radius: f32,
+	pub button_margin: f32,
+	pub orbit_sensitivity: f32,
+	pub zoom_sensitivity: f32,
+	joystick: Option<(u64, Vec2)>,
+}
+
+impl Default for TouchControls {
+	fn default() -> Self {
+		Self {
+			enable: cfg!(any(target_os = "android", target_os = "ios")),
+			joystick_radius: 80.,
+			knob_radius: 30.,
+			button_radius: 45.,
+			button_margin: 30.,
+			orbit_sensitivity: 1.,
+			zoom_sensitivity: 0.01,
+			joystick: None,
+		}
+	}
+}
+
+impl TouchControls {
+	/* buttons are laid out right to left from the bottom right corner */
+	fn button_center(&self, window_size: Vec2, slot: u32) -> Vec2 {
+		let offset = self.button_margin + self.button_radius + slot as f32 * (self.button_margin + 2. * self.button_radius);
+		Vec2::new(window_size.x - offset, window_size.y - self.button_margin - self.button_radius)
+	}
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct TouchButton {
+	pub action: CharacterAction,
+	pub slot: u32,
+}
+
+#[derive(Component)]
+pub(super) struct TouchJoystickBase;
+
+#[derive(Component)]
+pub(super) struct TouchJoystickKnob;
+
+pub(super) fn spawn_touch_controls(
+	mut commands: Commands,
+	controls: Res<TouchControls>,
+) {
+	let visibility = if controls.enable {
+		Visibility::Inherited
+	} else {
+		Visibility::Hidden
+	};
+	let circle = |radius: f32, color: Color| NodeBundle {
+		style: Style {
+			position_type: PositionType::Absolute,
+			width: Val::Px(2. * radius),
+			height: Val::Px(2. * radius),
+			align_items: AlignItems::Center,
+			justify_content: JustifyContent::Center,
+			..default()
+		},
+		background_color: color.into(),
+		border_radius: BorderRadius::MAX,
+		visibility: Visibility::Hidden,
+		..default()
+	};
+	commands.spawn((circle(controls.joystick_radius, JOYSTICK_COLOR), TouchJoystickBase));
+	commands.spawn((circle(controls.knob_radius, KNOB_COLOR), TouchJoystickKnob));
+	for (slot, (action, label)) in [(CharacterAction::Jump, "Jump"), (CharacterAction::Sprint, "Sprint")].into_iter().enumerate() {
+		let mut button = circle(controls.button_radius, BUTTON_COLOR);
+		let offset = controls.button_margin + slot as f32 * (controls.button_margin + 2. * controls.button_radius);
+		button.style.right = Val::Px(offset);
+		button.style.bottom = Val::Px(controls.button_margin);
+		button.visibility = visibility;
+		commands.spawn((button, TouchButton {
+			action,
+			slot: slot as u32,
+		})).with_children(|parent| {
+			parent.spawn(TextBundle::from_section(label, TextStyle {
+				font_size: 18.,
+				..default()
+			}));
+		});
+	}
+}
+
+pub(super) fn process_touch_input(
+	touches: Res<Touches>,
+	window_query: Query<&Window, With<PrimaryWindow>>,
+	button_query: Query<&TouchButton>,
+	bindings: Res<Persistent<Bindings>>,
+	context_stack: Res<InputContextStack>,
+	mut controls: ResMut<TouchControls>,
+	mut camera_input: ResMut<TouchCameraInput>,
+	mut action_events: EventWriter<PlayerAction>
+) {
+	let Ok(window) = window_query.get_single() else {
+		return;
+	};
+	if !controls.enable || context_stack.current() != InputContext::Gameplay {
+		return;
+	}
+	let Some(player) = bindings.get().players.iter().position(|profile| profile.touch).map(Player) else {
+		return;
+	};
+	let window_size = window.size();
+	for touch in touches.iter_just_released().chain(touches.iter_just_canceled()) {
+		if controls.joystick.is_some_and(|(id, _)| id == touch.id()) {
+			controls.joystick = None;
+		}
+	}
+	for touch in touches.iter_just_pressed() {
+		if controls.joystick.is_none() && touch.position().x < window_size.x / 2. {
+			controls.joystick = Some((touch.id(), touch.position()));
+		}
+	}
+
+	let mut actions: Vec<PlayerAction> = Vec::new();
+
+	/* joystick */
+	if let Some((id, origin)) = controls.joystick {
+		if let Some(touch) = touches.get_pressed(id) {
+			let offset = (touch.position() - origin) / controls.joystick_radius;
+			/* screen y points down */
+			let stick = bindings.get().analog.apply(Vec2::new(offset.x, -offset.y).clamp_length_max(1.));
+			if stick != Vec2::ZERO {
+				actions.push(PlayerAction::movement(player, stick));
+			}
+		}
+	}
+
+	/* buttons hold their action while the touch that started on them lasts */
+	let button_at = |position: Vec2| button_query.iter()
+		.find(|button| controls.button_center(window_size, button.slot).distance(position) <= controls.button_radius);
+	let mut look: Vec<&Touch> = Vec::new();
+	for touch in touches.iter() {
+		if controls.joystick.is_some_and(|(id, _)| id == touch.id()) {
+			continue;
+		}
+		match button_at(touch.start_position()) {
+			Some(button) => actions.push(PlayerAction::new(player, button.action)),
+			None => look.push(touch),
+		}
+	}
+
+	/* camera */
+	match look.as_slice() {
+		[touch] => camera_input.orbit += touch.delta() * controls.orbit_sensitivity,
+		[first, second] => {
+			let distance = first.position().distance(second.position());
+			let previous_distance = first.previous_position().distance(second.previous_position());
+			camera_input.zoom += (distance - previous_distance) * controls.zoom_sensitivity;
+		},
+		_ => (),
+	}
+
+	action_events.send_batch(actions);
+}
+
+pub(super) fn update_touch_joystick(
+	controls: Res<TouchControls>,
+	touches: Res<Touches>,
+	mut base_query: Query<(&mut Style, &mut Visibility), (With<TouchJoystickBase>, Without<TouchJoystickKnob>)>,
+	mut knob_query: Query<(&mut Style, &mut Visibility), (With<TouchJoystickKnob>, Without<TouchJoystickBase>)>,
+) {
+	let joystick = controls.joystick
+		.and_then(|(id, origin)| touches.get_pressed(id).map(|touch| (origin, touch.position())));
+	for (mut style, mut visibility) in base_query.iter_mut() {
+		match joystick {
+			Some((origin, _)) => {
+				style.left = Val::Px(origin.x - controls.joystick_radius);
+				style.top = Val::Px(origin.y - controls.joystick_radius);
+				*visibility = Visibility::Inherited;
+			},
+			None => *visibility = Visibility::Hidden,
+		}
+	}
+	for (mut style, mut visibility) in knob_query.iter_mut() {
+		match joystick {
+			Some((origin, position)) => {
+				let knob = origin + (position - origin).clamp_length_max(controls.joystick_radius);
+				style.left = Val::Px(knob.x - controls.knob_radius);
+				style.top = Val::Px(knob.y - controls.knob_radius);
+				*visibility = Visibility::Inherited;
+			},
+			None => *visibility = Visibility::Hidden,
+		}
+	}
+}
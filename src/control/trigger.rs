@@ -0,0 +1,255 @@
+use super::{
+	bindings::InputSource,
+	player::Player,
+};
+
+use bevy::{
+	prelude::*,
+	utils::{
+		HashMap,
+		HashSet
+	},
+};
+use serde::{
+	Serialize,
+	Deserialize
+};
+
+use character::CharacterAction;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Trigger {
+	/* every frame the input is held */
+	#[default]
+	Pressed,
+	JustPressed,
+	Released,
+	/* every frame after the input has been held for the given seconds */
+	Hold(f32),
+	/* once when pressed twice within the given seconds */
+	DoubleTap(f32),
+	/* every frame between two presses */
+	Toggle,
+}
+
+/* a plain action in the bindings file is shorthand for a pressed trigger */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(from = "BindingRepr", into = "BindingRepr")]
+pub struct ActionBinding {
+	pub action: CharacterAction,
+	pub trigger: Trigger,
+}
+
+impl From<CharacterAction> for ActionBinding {
+	fn from(action: CharacterAction) -> Self {
+		Self {
+			action,
+			trigger: Trigger::default(),
+		}
+	}
+}
+
+impl ActionBinding {
+	pub fn new(action: CharacterAction, trigger: Trigger) -> Self {
+		Self {
+			action,
+			trigger,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BindingRepr {
+	Action(CharacterAction),
+	Binding {
+		action: CharacterAction,
+		#[serde(default)]
+		trigger: Trigger,
+	},
+}
+
+impl From<BindingRepr> for ActionBinding {
+	fn from(repr: BindingRepr) -> Self {
+		match repr {
+			BindingRepr::Action(action) => action.into(),
+			BindingRepr::Binding { action, trigger } => Self::new(action, trigger),
+		}
+	}
+}
+
+impl From<ActionBinding> for BindingRepr {
+	fn from(binding: ActionBinding) -> Self {
+		match binding.trigger {
+			Trigger::Pressed => BindingRepr::Action(binding.action),
+			trigger => BindingRepr::Binding {
+				action: binding.action,
+				trigger,
+			},
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct TriggerInput {
+	pub pressed: bool,
+	pub just_pressed: bool,
+	pub just_released: bool,
+}
+
+impl TriggerInput {
+	pub fn of<T: Copy + Eq + std::hash::Hash + Send + Sync + 'static>(input: &ButtonInput<T>, button: T) -> Self {
+		Self {
+			pressed: input.pressed(button),
+			just_pressed: input.just_pressed(button),
+			just_released: input.just_released(button),
+		}
+	}
+}
+
+/* timers and toggles per player and input, gamepad inputs are tracked per gamepad */
+type TriggerSource = (Player, Option<Gamepad>, InputSource);
+
+#[derive(Resource, Default)]
+pub(super) struct TriggerState {
+	held: HashMap<TriggerSource, f32>,
+	taps: HashMap<TriggerSource, f32>,
+	toggled: HashSet<TriggerSource>,
+}
+
+impl TriggerState {
+	pub fn fires(&mut self, source: TriggerSource, trigger: Trigger, input: TriggerInput, time: &Time) -> bool {
+		match trigger {
+			Trigger::Pressed => input.pressed,
+			Trigger::JustPressed => input.just_pressed,
+			Trigger::Released => input.just_released,
+			Trigger::Hold(duration) => {
+				if input.pressed {
+					let held = self.held.entry(source).or_default();
+					*held += time.delta_seconds();
+					*held >= duration
+				} else {
+					self.held.remove(&source);
+					false
+				}
+			},
+			Trigger::DoubleTap(interval) => {
+				if !input.just_pressed {
+					return false;
+				}
+				let now = time.elapsed_seconds();
+				match self.taps.insert(source, now) {
+					Some(previous) if now - previous <= interval => {
+						self.taps.remove(&source);
+						true
+					},
+					_ => false,
+				}
+			},
+			Trigger::Toggle => {
+				if input.just_pressed && !self.toggled.remove(&source) {
+					self.toggled.insert(source);
+				}
+				self.toggled.contains(&source)
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::time::Duration;
+
+	const SOURCE: TriggerSource = (Player(0), None, InputSource::Key(KeyCode::Space));
+
+	const PRESS: TriggerInput = TriggerInput {
+		pressed: true,
+		just_pressed: true,
+		just_released: false,
+	};
+	const HOLD: TriggerInput = TriggerInput {
+		pressed: true,
+		just_pressed: false,
+		just_released: false,
+	};
+	const RELEASE: TriggerInput = TriggerInput {
+		pressed: false,
+		just_pressed: false,
+		just_released: true,
+	};
+	const IDLE: TriggerInput = TriggerInput {
+		pressed: false,
+		just_pressed: false,
+		just_released: false,
+	};
+
+	/* advances the clock by `seconds` before evaluating the trigger */
+	fn step(state: &mut TriggerState, time: &mut Time, seconds: f32, trigger: Trigger, input: TriggerInput) -> bool {
+		time.advance_by(Duration::from_secs_f32(seconds));
+		state.fires(SOURCE, trigger, input, time)
+	}
+
+	#[test]
+	fn hold_fires_after_duration() {
+		let (mut state, mut time) = (TriggerState::default(), Time::default());
+		let trigger = Trigger::Hold(0.5);
+		assert!(!step(&mut state, &mut time, 0.1, trigger, PRESS));
+		assert!(!step(&mut state, &mut time, 0.3, trigger, HOLD));
+		assert!(step(&mut state, &mut time, 0.2, trigger, HOLD));
+		assert!(step(&mut state, &mut time, 0.1, trigger, HOLD));
+	}
+
+	#[test]
+	fn hold_resets_on_release() {
+		let (mut state, mut time) = (TriggerState::default(), Time::default());
+		let trigger = Trigger::Hold(0.5);
+		assert!(!step(&mut state, &mut time, 0.4, trigger, PRESS));
+		assert!(!step(&mut state, &mut time, 0.1, trigger, RELEASE));
+		assert!(!step(&mut state, &mut time, 0.4, trigger, PRESS));
+		assert!(step(&mut state, &mut time, 0.2, trigger, HOLD));
+	}
+
+	#[test]
+	fn double_tap_fires_within_interval() {
+		let (mut state, mut time) = (TriggerState::default(), Time::default());
+		let trigger = Trigger::DoubleTap(0.3);
+		assert!(!step(&mut state, &mut time, 0.1, trigger, PRESS));
+		assert!(!step(&mut state, &mut time, 0.1, trigger, RELEASE));
+		assert!(step(&mut state, &mut time, 0.1, trigger, PRESS));
+		/* a third tap starts a new pair */
+		assert!(!step(&mut state, &mut time, 0.1, trigger, PRESS));
+	}
+
+	#[test]
+	fn double_tap_ignores_slow_taps() {
+		let (mut state, mut time) = (TriggerState::default(), Time::default());
+		let trigger = Trigger::DoubleTap(0.3);
+		assert!(!step(&mut state, &mut time, 0.1, trigger, PRESS));
+		assert!(!step(&mut state, &mut time, 0.5, trigger, PRESS));
+		/* the slow tap still counts as the first of the next pair */
+		assert!(step(&mut state, &mut time, 0.2, trigger, PRESS));
+	}
+
+	#[test]
+	fn toggle_flips_on_each_press() {
+		let (mut state, mut time) = (TriggerState::default(), Time::default());
+		let trigger = Trigger::Toggle;
+		assert!(!step(&mut state, &mut time, 0.1, trigger, IDLE));
+		assert!(step(&mut state, &mut time, 0.1, trigger, PRESS));
+		assert!(step(&mut state, &mut time, 0.1, trigger, RELEASE));
+		assert!(step(&mut state, &mut time, 1., trigger, IDLE));
+		assert!(!step(&mut state, &mut time, 0.1, trigger, PRESS));
+		assert!(!step(&mut state, &mut time, 0.1, trigger, IDLE));
+	}
+
+	#[test]
+	fn triggers_are_tracked_per_player() {
+		let (mut state, mut time) = (TriggerState::default(), Time::default());
+		time.advance_by(Duration::from_secs_f32(0.1));
+		let other = (Player(1), None, SOURCE.2);
+		assert!(state.fires(SOURCE, Trigger::Toggle, PRESS, &time));
+		assert!(!state.fires(other, Trigger::Toggle, IDLE, &time));
+	}
+}
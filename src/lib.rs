@@ -16,6 +16,23 @@ use self::{
 use bevy::prelude::*;
 use avian3d::debug_render::PhysicsDebugPlugin;
 
+pub use control::{
+	InputRecorder,
+	InputRecording,
+	InputFrame,
+	RecorderPlugin,
+	PlayerAction,
+	CameraMode,
+	Controlling,
+	Player,
+	PlayerCamera,
+	SplitCamera,
+	LockOn,
+	LockOnAction,
+	LockOnPlugin,
+	LockOnTarget,
+};
+
 pub struct BasePlugin;
 
 impl Plugin for BasePlugin {
@@ -0,0 +1,231 @@
+use crate::control::{
+	Bindings,
+	BindingTable,
+	InputContext,
+	InputContextStack,
+	InputSource,
+	Player,
+	Trigger,
+	BINDABLE_ACTIONS
+};
+
+use bevy::prelude::*;
+
+use bevy_egui::{
+	egui,
+	EguiContexts
+};
+
+use bevy_persistent::prelude::*;
+
+use character::CharacterAction;
+
+#[derive(Resource, Default)]
+pub(super) enum RebindState {
+	#[default]
+	Idle,
+	/* waiting for the next key, mouse or gamepad button press */
+	Capturing(CharacterAction),
+	/* the captured input is already bound to another action */
+	Conflict(CharacterAction, InputSource, CharacterAction),
+	/* gameplay stays blocked until the captured input is let go */
+	Releasing(InputSource),
+}
+
+/* the table the menu edits, players without their own gameplay table get a copy on first edit */
+#[derive(Resource, Default)]
+pub(super) struct BindingsMenuState {
+	context: InputContext,
+	player: Option<Player>,
+	error: Option<String>,
+}
+
+impl BindingsMenuState {
+	/* a failed save leaves the game running, the menu shows what went wrong */
+	fn report<E: std::fmt::Display>(&mut self, operation: &str, result: Result<(), E>) {
+		match result {
+			Ok(()) => self.error = None,
+			Err(error) => {
+				error!("failed to {operation} key bindings: {error}");
+				self.error = Some(format!("Failed to {operation} key bindings: {error}"));
+			},
+		}
+	}
+}
+
+/* clicks on the menu itself are never captured, so its buttons stay usable */
+fn captured_source(
+	key_input: &ButtonInput<KeyCode>,
+	mouse_input: &ButtonInput<MouseButton>,
+	gamepad_input: &ButtonInput<GamepadButton>,
+	pointer_over_menu: bool,
+) -> Option<InputSource> {
+	let mouse = mouse_input.get_just_pressed().next().filter(|_| !pointer_over_menu);
+	key_input.get_just_pressed().next().map(|&key| InputSource::Key(key))
+		.or(mouse.map(|&button| InputSource::Mouse(button)))
+		.or(gamepad_input.get_just_pressed().next().map(|button| InputSource::Gamepad(button.button_type)))
+}
+
+fn source_pressed(
+	key_input: &ButtonInput<KeyCode>,
+	mouse_input: &ButtonInput<MouseButton>,
+	gamepad_input: &ButtonInput<GamepadButton>,
+	source: InputSource,
+) -> bool {
+	match source {
+		InputSource::Key(key) => key_input.pressed(key),
+		InputSource::Mouse(button) => mouse_input.pressed(button),
+		InputSource::Gamepad(button_type) => gamepad_input.get_pressed().any(|button| button.button_type == button_type),
+	}
+}
+
+fn edited_table(bindings: &Bindings, context: InputContext, player: Option<Player>) -> Option<&BindingTable> {
+	match player {
+		Some(player) => bindings.player_table(player, context),
+		None => bindings.table(context),
+	}
+}
+
+fn table_label(context: InputContext, player: Option<Player>) -> String {
+	match (context, player) {
+		(InputContext::Gameplay, Some(player)) => format!("Player {}", player.0 + 1),
+		(context, _) => format!("{context:?}"),
+	}
+}
+
+pub(super) fn load_bindings_menu(
+	mut contexts: EguiContexts,
+	mut bindings: ResMut<Persistent<Bindings>>,
+	mut state: ResMut<RebindState>,
+	mut menu_state: ResMut<BindingsMenuState>,
+	mut context_stack: ResMut<InputContextStack>,
+	key_input: Res<ButtonInput<KeyCode>>,
+	mouse_input: Res<ButtonInput<MouseButton>>,
+	gamepad_input: Res<ButtonInput<GamepadButton>>,
+) {
+	let (context, player) = (menu_state.context, menu_state.player);
+	match *state {
+		RebindState::Capturing(action) => {
+			let pointer_over_menu = contexts.ctx_mut().is_pointer_over_area();
+			if key_input.just_pressed(KeyCode::Escape) {
+				*state = RebindState::Releasing(InputSource::Key(KeyCode::Escape));
+			} else if let Some(source) = captured_source(&key_input, &mouse_input, &gamepad_input, pointer_over_menu) {
+				*state = match edited_table(bindings.get(), context, player).and_then(|table| table.action(source)) {
+					Some(bound) if bound == action => RebindState::Releasing(source),
+					Some(bound) => RebindState::Conflict(action, source, bound),
+					None => {
+						menu_state.report("save", bindings.update(|bindings| {
+							bindings.player_table_mut(player, context).bind(source, action);
+						}));
+						RebindState::Releasing(source)
+					},
+				};
+			}
+		},
+		RebindState::Releasing(source) if !source_pressed(&key_input, &mouse_input, &gamepad_input, source) => {
+			*state = RebindState::Idle;
+		},
+		_ => (),
+	}
+
+	/* keep captured presses away from gameplay */
+	if state.is_changed() {
+		match *state {
+			RebindState::Idle => {
+				context_stack.pop(InputContext::Menu);
+			},
+			_ if context_stack.current() != InputContext::Menu => context_stack.push(InputContext::Menu),
+			_ => (),
+		}
+	}
+
+	let player_count = bindings.get().players.len();
+	egui::Window::new("Bindings").default_open(false).show(contexts.ctx_mut(), |ui| {
+		let tables = std::iter::once((InputContext::Gameplay, None))
+			.chain((0..player_count).map(|index| (InputContext::Gameplay, Some(Player(index)))))
+			.chain([InputContext::Menu, InputContext::Editor, InputContext::FreeCamera].map(|context| (context, None)));
+		egui::ComboBox::from_label("Table")
+			.selected_text(table_label(context, player))
+			.show_ui(ui, |ui| {
+				for (table_context, table_player) in tables {
+					let selected = (table_context, table_player) == (context, player);
+					if ui.selectable_label(selected, table_label(table_context, table_player)).clicked() && !selected {
+						menu_state.context = table_context;
+						menu_state.player = table_player;
+						*state = RebindState::Idle;
+					}
+				}
+			});
+
+		let mut unbind = None;
+		let table = edited_table(bindings.get(), context, player);
+		egui::Grid::new("bindings_grid").striped(true).show(ui, |ui| {
+			for action in BINDABLE_ACTIONS {
+				ui.label(format!("{action:?}"));
+				ui.horizontal(|ui| {
+					for source in table.map(|table| table.sources(action)).unwrap_or_default() {
+						let label = match table.and_then(|table| table.binding(source)).map(|binding| binding.trigger) {
+							Some(Trigger::Pressed) | None => source_label(source),
+							Some(trigger) => format!("{} ({trigger:?})", source_label(source)),
+						};
+						if ui.button(format!("{label}  ✕")).on_hover_text("Remove binding").clicked() {
+							unbind = Some(source);
+						}
+					}
+					let capturing = matches!(*state, RebindState::Capturing(capturing) if capturing == action);
+					if ui.selectable_label(capturing, "+").on_hover_text("Add binding").clicked() {
+						*state = RebindState::Capturing(action);
+					}
+				});
+				ui.end_row();
+			}
+		});
+		if let Some(source) = unbind {
+			menu_state.report("save", bindings.update(|bindings| {
+				bindings.player_table_mut(player, context).unbind(source);
+			}));
+		}
+
+		ui.separator();
+		match *state {
+			RebindState::Idle | RebindState::Releasing(_) => (),
+			RebindState::Capturing(action) => {
+				ui.horizontal(|ui| {
+					ui.label(format!("Press a key, mouse or gamepad button for {action:?}"));
+					if ui.button("Cancel").clicked() {
+						*state = RebindState::Idle;
+					}
+				});
+			},
+			RebindState::Conflict(action, source, bound) => {
+				ui.label(format!("{} is already bound to {bound:?}", source_label(source)));
+				ui.horizontal(|ui| {
+					if ui.button(format!("Rebind to {action:?}")).clicked() {
+						menu_state.report("save", bindings.update(|bindings| {
+							bindings.player_table_mut(player, context).bind(source, action);
+						}));
+						*state = RebindState::Idle;
+					}
+					if ui.button("Cancel").clicked() {
+						*state = RebindState::Idle;
+					}
+				});
+			},
+		}
+		if ui.button("Revert to defaults").clicked() {
+			menu_state.report("revert", bindings.revert_to_default());
+			*state = RebindState::Idle;
+		}
+		if let Some(error) = menu_state.error.as_ref() {
+			ui.colored_label(egui::Color32::RED, error);
+		}
+	});
+}
+
+fn source_label(source: InputSource) -> String {
+	match source {
+		InputSource::Key(key) => format!("{key:?}"),
+		InputSource::Mouse(button) => format!("Mouse {button:?}"),
+		InputSource::Gamepad(button) => format!("Gamepad {button:?}"),
+	}
+}
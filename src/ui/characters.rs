@@ -0,0 +1,64 @@
+use super::Selected;
+
+use crate::control::{
+	Controlling,
+	Player,
+	Possess,
+	PlayCinematic
+};
+
+use bevy::prelude::*;
+
+use bevy_egui::{
+	egui,
+	EguiContexts
+};
+
+use character::CharacterController;
+
+const SHOWCASE_PATH: &str = "cinematics/showcase.shots.ron";
+
+pub(super) fn load_characters_menu(
+	mut contexts: EguiContexts,
+	mut commands: Commands,
+	character_query: Query<(Entity, Option<&Name>, Option<&Controlling>, Has<Selected>), With<CharacterController>>,
+	mut possess_events: EventWriter<Possess>,
+	mut cinematic_events: EventWriter<PlayCinematic>,
+	asset_server: Res<AssetServer>,
+) {
+	egui::Window::new("Characters").default_open(false).show(contexts.ctx_mut(), |ui| {
+		let mut characters: Vec<_> = character_query.iter().collect();
+		characters.sort_by_key(|&(entity, ..)| entity);
+		egui::Grid::new("characters_grid").striped(true).show(ui, |ui| {
+			for (entity, name, controlling, selected) in characters {
+				match name {
+					Some(name) => ui.label(name.as_str()),
+					None => ui.label(format!("{entity}")),
+				};
+				match controlling {
+					Some(controlling) => ui.label(format!("Player {}", controlling.0.0 + 1)),
+					None => ui.label(""),
+				};
+				/* the menu possesses for the first player */
+				if ui.add_enabled(controlling.is_none(), egui::Button::new("Possess")).clicked() {
+					possess_events.send(Possess {
+						player: Player(0),
+						entity,
+					});
+				}
+				if ui.add_enabled(!selected, egui::Button::new("Select")).clicked() {
+					for (other, .., other_selected) in character_query.iter() {
+						if other_selected {
+							commands.entity(other).remove::<Selected>();
+						}
+					}
+					commands.entity(entity).insert(Selected);
+				}
+				ui.end_row();
+			}
+		});
+		if ui.button("Showcase").clicked() {
+			cinematic_events.send(PlayCinematic(asset_server.load(SHOWCASE_PATH)));
+		}
+	});
+}
@@ -1,6 +1,17 @@
-use super::metadata::{
-	AssetMetadata,
-	AssetTarget
+mod bindings;
+mod recorder;
+mod characters;
+
+use bindings::*;
+use recorder::*;
+use characters::*;
+
+use super::{
+	metadata::{
+		AssetMetadata,
+		AssetTarget
+	},
+	control::InputSet,
 };
 
 use bevy::prelude::*;
@@ -23,9 +34,15 @@ impl Plugin for UiPlugin {
 		app.add_plugins(EguiPlugin)
 			.insert_state(CharacterMenuTab::General)
 			.insert_state(LoadMenuState::Open(AssetTarget::Body))
+			.init_resource::<RebindState>()
+			.init_resource::<BindingsMenuState>()
+			.init_resource::<RecorderMenuState>()
 			.add_systems(Update, (
 				load_loader_menu,
 				load_edit_menu,
+				load_bindings_menu.after(InputSet),
+				load_recorder_menu,
+				load_characters_menu,
 			)
 		);
 	}
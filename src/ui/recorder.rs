@@ -0,0 +1,64 @@
+use crate::control::{
+	InputRecorder,
+	InputRecording,
+	DEFAULT_RECORDING_PATH
+};
+
+use bevy::prelude::*;
+
+use bevy_egui::{
+	egui,
+	EguiContexts
+};
+
+#[derive(Resource)]
+pub(super) struct RecorderMenuState {
+	path: String,
+	status: String,
+}
+
+impl Default for RecorderMenuState {
+	fn default() -> Self {
+		Self {
+			path: DEFAULT_RECORDING_PATH.to_owned(),
+			status: String::new(),
+		}
+	}
+}
+
+pub(super) fn load_recorder_menu(
+	mut contexts: EguiContexts,
+	mut recorder: ResMut<InputRecorder>,
+	mut menu_state: ResMut<RecorderMenuState>,
+) {
+	egui::Window::new("Recorder").default_open(false).show(contexts.ctx_mut(), |ui| {
+		let RecorderMenuState { path, status } = menu_state.as_mut();
+		ui.text_edit_singleline(path);
+		ui.horizontal(|ui| {
+			if recorder.is_recording() || recorder.is_replaying() {
+				if ui.button("Stop").clicked() {
+					*status = match recorder.stop() {
+						Ok(Some(saved)) => format!("saved {}", saved.display()),
+						Ok(None) => "stopped".to_owned(),
+						Err(error) => error,
+					};
+				}
+				return;
+			}
+			if ui.button("Record").clicked() {
+				recorder.start_recording(path.as_str());
+				*status = "recording".to_owned();
+			}
+			if ui.button("Replay").clicked() {
+				match InputRecording::load(path.as_str()) {
+					Ok(recording) => {
+						*status = format!("replaying {:.1}s", recording.duration());
+						recorder.start_replay(recording);
+					},
+					Err(error) => *status = error,
+				}
+			}
+		});
+		ui.label(status.as_str());
+	});
+}
@@ -36,6 +36,16 @@ impl Character for DebugCharacter {
 		mass_properties.center_of_mass.y = self.height / 2.0;
 		mass_properties
 	}
+	fn jump_system(&self) -> JumpSystem {
+		JumpSystem::new(0.6, 1, 0.12, 0.15)
+	}
+	/* keeps standing on ledge edges the centre ray would slip off */
+	fn ground_probe(&self) -> GroundProbe {
+		GroundProbe::Ring {
+			radius: 0.15,
+			count: 6,
+		}
+	}
 	fn collider_constructor_hierarchy() -> ColliderConstructorHierarchy {
 		let mut config = HashMap::new();
 		config.insert(
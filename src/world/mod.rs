@@ -4,7 +4,12 @@ use super::{
 	ui::Selected,
 	control::{
 		Controlling,
-		CameraMode
+		CameraMode,
+		Player,
+		PlayerCamera,
+		CameraCollision,
+		CameraRig,
+		Shake
 	},
 };
 
@@ -28,6 +33,10 @@ use world::*;
 
 const DEFAULT_CAMERA_TRANSFORM: Transform = Transform::from_xyz(0., 1., 2.);
 const DEFAULT_CURSOR_TRANSFORM: Transform = Transform::from_xyz(0., 1., 0.);
+const CHARACTER_COUNT: usize = 3;
+/* deeper than a character is tall, below the last debug character so it drops in and swims */
+const POOL_TRANSFORM: Transform = Transform::from_xyz(3., 1.5, 0.);
+const POOL_SIZE: Vec3 = Vec3::new(4., 3., 4.);
 
 pub struct WorldPlugin;
 
@@ -39,6 +48,7 @@ impl Plugin for WorldPlugin {
 		))
 			.add_systems(Startup, (
 				setup_environment,
+				spawn_pool,
 			))
 			.add_systems(Startup, spawn_character);
 	}
@@ -82,6 +92,22 @@ fn setup_environment(
 	// });
 }
 
+fn spawn_pool(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>
+) {
+	commands.spawn(WaterBundle::new(POOL_TRANSFORM, POOL_SIZE))
+		.insert((
+			meshes.add(Cuboid::from_size(POOL_SIZE)),
+			materials.add(StandardMaterial {
+				base_color: Color::srgba(0.1, 0.3, 0.6, 0.4),
+				alpha_mode: AlphaMode::Blend,
+				..default()
+			}),
+			Name::new("Pool"),
+		));
+}
 
 fn spawn_character(
 	mut commands: Commands,
@@ -89,10 +115,16 @@ fn spawn_character(
 	mut meshes: ResMut<Assets<Mesh>>,
 	mut materials: ResMut<Assets<StandardMaterial>>
 ) {
-	commands.spawn(Camera3dBundle{
-		transform: DEFAULT_CAMERA_TRANSFORM.looking_at(DEFAULT_CURSOR_TRANSFORM.translation, Vec3::Y),
-		..Default::default()
-	});
+	commands.spawn((
+		Camera3dBundle{
+			transform: DEFAULT_CAMERA_TRANSFORM.looking_at(DEFAULT_CURSOR_TRANSFORM.translation, Vec3::Y),
+			..Default::default()
+		},
+		PlayerCamera(Player(0)),
+		CameraCollision::default(),
+		CameraRig::default(),
+		Shake::default(),
+	));
 	let target = commands.spawn((
 		PbrBundle {
 			transform: DEFAULT_CURSOR_TRANSFORM,
@@ -106,9 +138,19 @@ fn spawn_character(
 			height: 1.5,
 		}),
 		SpatialBundle::from_transform(Transform::from_xyz(0., 10., 0.)),
-		Controlling,
+		Name::new("Debug 0"),
+		Controlling(Player(0)),
 		Selected
 	));
+	for i in 1..CHARACTER_COUNT {
+		commands.spawn((
+			CharacterBundle::new(DebugCharacter {
+				height: 1.5,
+			}),
+			SpatialBundle::from_transform(Transform::from_xyz(i as f32 * 1.5, 10., 0.)),
+			Name::new(format!("Debug {i}")),
+		));
+	}
 		// parent.spawn(CharacterPartBundle::head(asset_server.load("character/head/base.gltf#Scene0")));
 	commands.insert_resource(State::new(CameraMode::FreeFollowing(target)));
 }
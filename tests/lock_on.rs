@@ -0,0 +1,121 @@
+use bevy::{
+	prelude::*,
+	state::app::StatesPlugin,
+};
+use avian3d::prelude::*;
+
+use character::CharacterController;
+
+use okfd::{
+	CameraMode,
+	Controlling,
+	LockOn,
+	LockOnAction,
+	LockOnPlugin,
+	LockOnTarget,
+	Player,
+	PlayerCamera,
+	SplitCamera
+};
+
+fn spawn_character(app: &mut App, position: Vec3) -> Entity {
+	app.world_mut().spawn((
+		CharacterController::default(),
+		Transform::from_translation(position),
+		GlobalTransform::from_translation(position),
+	)).id()
+}
+
+/* a camera behind the controlled character, looking down -z */
+fn lock_on_app() -> (App, Entity) {
+	let mut app = App::new();
+	app.add_plugins((MinimalPlugins, StatesPlugin, LockOnPlugin))
+		.init_state::<CameraMode>()
+		.insert_resource(SpatialQueryPipeline::default());
+	app.world_mut().spawn((
+		Camera3dBundle {
+			transform: Transform::from_xyz(0., 2., 5.).looking_to(Vec3::NEG_Z, Vec3::Y),
+			..default()
+		},
+		PlayerCamera(Player(0)),
+	));
+	let character = spawn_character(&mut app, Vec3::ZERO);
+	app.world_mut().entity_mut(character).insert(Controlling(Player(0)));
+	app.update();
+	(app, character)
+}
+
+fn send(app: &mut App, player: Player, action: LockOnAction) {
+	app.world_mut().send_event(LockOn {
+		player,
+		action,
+	});
+	/* the system requests the mode, the next frame applies it */
+	app.update();
+	app.update();
+}
+
+fn mode(app: &App) -> CameraMode {
+	app.world().resource::<State<CameraMode>>().get().clone()
+}
+
+#[test]
+fn lock_on_engages_nearest_target() {
+	let (mut app, character) = lock_on_app();
+	spawn_character(&mut app, Vec3::new(0., 0., -8.));
+	let near = spawn_character(&mut app, Vec3::new(1., 0., -4.));
+	send(&mut app, Player(0), LockOnAction::Toggle);
+	assert_eq!(mode(&app), CameraMode::LockOn(character, near));
+}
+
+#[test]
+fn lock_on_ignores_targets_out_of_view() {
+	let (mut app, _) = lock_on_app();
+	spawn_character(&mut app, Vec3::new(0., 0., 8.));
+	send(&mut app, Player(0), LockOnAction::Toggle);
+	assert_eq!(mode(&app), CameraMode::Tracked);
+}
+
+#[test]
+fn lock_on_cycles_and_releases() {
+	let (mut app, character) = lock_on_app();
+	let near = spawn_character(&mut app, Vec3::new(1., 0., -4.));
+	let far = spawn_character(&mut app, Vec3::new(-1., 0., -8.));
+	send(&mut app, Player(0), LockOnAction::Toggle);
+	assert_eq!(mode(&app), CameraMode::LockOn(character, near));
+	send(&mut app, Player(0), LockOnAction::Cycle);
+	assert_eq!(mode(&app), CameraMode::LockOn(character, far));
+	send(&mut app, Player(0), LockOnAction::Cycle);
+	assert_eq!(mode(&app), CameraMode::LockOn(character, near));
+	send(&mut app, Player(0), LockOnAction::Toggle);
+	assert_eq!(mode(&app), CameraMode::Following(character));
+}
+
+#[test]
+fn second_player_locks_on_with_their_own_camera() {
+	let (mut app, _) = lock_on_app();
+	/* a split camera off to the side, looking down +x */
+	let camera = app.world_mut().spawn((
+		Camera3dBundle {
+			transform: Transform::from_xyz(-5., 2., 10.).looking_to(Vec3::X, Vec3::Y),
+			..default()
+		},
+		PlayerCamera(Player(1)),
+		SplitCamera,
+	)).id();
+	let character = spawn_character(&mut app, Vec3::new(-3., 0., 10.));
+	app.world_mut().entity_mut(character).insert(Controlling(Player(1)));
+	let target = spawn_character(&mut app, Vec3::new(2., 0., 10.));
+	app.update();
+
+	send(&mut app, Player(1), LockOnAction::Toggle);
+	assert_eq!(app.world().get::<LockOnTarget>(camera), Some(&LockOnTarget {
+		character,
+		target,
+	}));
+	/* the first player's camera is left alone */
+	assert_eq!(mode(&app), CameraMode::Tracked);
+
+	send(&mut app, Player(1), LockOnAction::Toggle);
+	assert_eq!(app.world().get::<LockOnTarget>(camera), None);
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use bevy::{
+	prelude::*,
+	time::TimeUpdateStrategy,
+};
+
+use character::CharacterAction;
+
+use okfd::{
+	InputRecorder,
+	InputRecording,
+	Player,
+	PlayerAction,
+	RecorderPlugin
+};
+
+const TIMESTEP: f32 = 1. / 60.;
+/* the app's own step, the recorder swaps it for the recording's while active */
+const APP_TIMESTEP: f32 = 1. / 30.;
+
+/* actions seen each tick, along with the delta the tick ran with */
+#[derive(Resource, Default)]
+struct Emitted(Vec<(f32, Vec<PlayerAction>)>);
+
+fn collect_actions(
+	mut action_events: EventReader<PlayerAction>,
+	mut emitted: ResMut<Emitted>,
+	time: Res<Time>,
+) {
+	let actions = action_events.read().copied().collect();
+	emitted.0.push((time.delta_seconds(), actions));
+}
+
+fn script() -> Vec<Vec<PlayerAction>> {
+	let player = Player(0);
+	vec![
+		vec![PlayerAction::new(player, CharacterAction::Forward)],
+		vec![PlayerAction::new(player, CharacterAction::Forward), PlayerAction::new(player, CharacterAction::Sprint)],
+		vec![],
+		vec![PlayerAction::new(player, CharacterAction::Jump)],
+		vec![PlayerAction::movement(player, Vec2::new(0.5, -0.25))],
+		vec![PlayerAction::new(player, CharacterAction::Crouch), PlayerAction::new(Player(1), CharacterAction::Left)],
+	]
+}
+
+fn replay_app() -> App {
+	let mut app = App::new();
+	app.add_plugins((MinimalPlugins, RecorderPlugin))
+		.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(APP_TIMESTEP)))
+		.init_resource::<Emitted>()
+		.add_systems(PostUpdate, collect_actions);
+	app
+}
+
+fn app_timestep_restored(app: &App) -> bool {
+	matches!(
+		app.world().resource::<TimeUpdateStrategy>(),
+		TimeUpdateStrategy::ManualDuration(duration) if *duration == Duration::from_secs_f32(APP_TIMESTEP)
+	)
+}
+
+#[test]
+fn replay_emits_recorded_actions_on_the_same_ticks() {
+	let path = std::env::temp_dir().join("okfd_replay_test.ron");
+	let mut app = replay_app();
+	/* lets the first tick's zero delta pass before recording */
+	app.update();
+
+	app.world_mut().resource_mut::<InputRecorder>().start_recording(&path);
+	app.world_mut().resource_mut::<Emitted>().0.clear();
+	for actions in script() {
+		app.world_mut().send_event_batch(actions);
+		app.update();
+	}
+	let saved = app.world_mut().resource_mut::<InputRecorder>().stop().unwrap();
+	assert_eq!(saved.as_deref(), Some(path.as_path()));
+	let recorded = std::mem::take(&mut app.world_mut().resource_mut::<Emitted>().0);
+	for (delta, _) in recorded.iter() {
+		assert!((delta - TIMESTEP).abs() < 1e-6);
+	}
+	app.update();
+	assert!(app_timestep_restored(&app));
+
+	let recording = InputRecording::load(&path).unwrap();
+	assert_eq!(recording.frames.len(), script().len());
+	assert_eq!(recording.timestep, TIMESTEP);
+
+	let mut app = replay_app();
+	app.update();
+	app.world_mut().resource_mut::<InputRecorder>().start_replay(recording);
+	app.world_mut().resource_mut::<Emitted>().0.clear();
+	for _ in script() {
+		app.update();
+	}
+	let replayed = std::mem::take(&mut app.world_mut().resource_mut::<Emitted>().0);
+	assert_eq!(replayed, recorded);
+	for (delta, _) in replayed {
+		assert!((delta - TIMESTEP).abs() < 1e-6);
+	}
+	/* the recorder goes idle once the frames run out */
+	app.update();
+	assert!(!app.world().resource::<InputRecorder>().is_replaying());
+	app.update();
+	assert!(app_timestep_restored(&app));
+	let _ = std::fs::remove_file(path);
+}
@@ -1,7 +1,49 @@
+use bevy::prelude::*;
 use avian3d::prelude::*;
 
 #[derive(PhysicsLayer)]
 pub enum SpatialTypes {
 	Character,
-	World
+	World,
+	Water,
+	/* character body volumes, kept out of the character layer so ground probes don't hit their own body */
+	Body
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WaterVolume {
+	pub density: f32,
+	pub drag: f32,
+}
+
+impl Default for WaterVolume {
+	fn default() -> Self {
+		Self {
+			density: 1.0,
+			drag: 2.0,
+		}
+	}
+}
+
+#[derive(Bundle)]
+pub struct WaterBundle {
+	pub water: WaterVolume,
+	pub spatial: SpatialBundle,
+	pub rigid_body: RigidBody,
+	pub collider: Collider,
+	pub sensor: Sensor,
+	pub layers: CollisionLayers,
+}
+
+impl WaterBundle {
+	pub fn new(transform: Transform, size: Vec3) -> Self {
+		Self {
+			water: WaterVolume::default(),
+			spatial: SpatialBundle::from_transform(transform),
+			rigid_body: RigidBody::Static,
+			collider: Collider::cuboid(size.x, size.y, size.z),
+			sensor: Sensor,
+			layers: CollisionLayers::new(SpatialTypes::Water, LayerMask::ALL),
+		}
+	}
 }